@@ -0,0 +1,155 @@
+//! Offline mock of the Pathbot API, serving a seeded maze.
+//!
+//! Usage: `pathbot-server [--port 8000] [--width 10] [--height 10] [--seed 42]`
+//!
+//! See docs/API.md for the endpoints.
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    server::run();
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    panic!("pathbot-server is a native binary.");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use log::*;
+    use pathbot::maze::{Maze, MazeError, MAX_ROOMS};
+    use pathbot::pathbot_api::*;
+    use serde::Deserialize;
+    use std::{env, process};
+    use tiny_http::{Header, Method, Request, Response, Server};
+
+    struct Options {
+        port: u16,
+        width: u32,
+        height: u32,
+        seed: u64,
+    }
+
+    impl Default for Options {
+        fn default() -> Self {
+            Options {
+                port: 8000,
+                width: 10,
+                height: 10,
+                seed: 42,
+            }
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct MoveRequest {
+        direction: MoveDirection,
+    }
+
+    pub fn run() {
+        env_logger::init();
+
+        let options = parse_args().unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            eprintln!(
+                "Usage: pathbot-server [--port PORT] [--width W] [--height H] [--seed SEED]"
+            );
+            process::exit(2);
+        });
+
+        let maze = Maze::generate(options.width, options.height, options.seed);
+        let server = Server::http(("0.0.0.0", options.port)).unwrap_or_else(|e| {
+            eprintln!("Cannot listen on port {}: {}", options.port, e);
+            process::exit(1);
+        });
+        info!(
+            "Serving a {}x{} maze (seed {}) on http://localhost:{}/pathbot/start",
+            maze.width(),
+            maze.height(),
+            options.seed,
+            options.port
+        );
+
+        for request in server.incoming_requests() {
+            handle(&maze, request);
+        }
+    }
+
+    fn parse_args() -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}.", arg))?;
+            let invalid = |_| format!("Invalid value for {}: {}", arg, value);
+            match arg.as_str() {
+                "--port" => options.port = value.parse().map_err(invalid)?,
+                "--width" => options.width = value.parse().map_err(invalid)?,
+                "--height" => options.height = value.parse().map_err(invalid)?,
+                "--seed" => options.seed = value.parse().map_err(invalid)?,
+                _ => return Err(format!("Unknown option: {}", arg)),
+            }
+        }
+        if !Maze::valid_size(options.width, options.height) {
+            return Err(format!(
+                "The maze must have between 1 and {} rooms.",
+                MAX_ROOMS
+            ));
+        }
+        Ok(options)
+    }
+
+    fn handle(maze: &Maze, mut request: Request) {
+        let (status, answer) = match (request.method(), request.url()) {
+            (Method::Options, _) => (204, None),
+            (Method::Post, "/pathbot/start") => (200, Some(PathbotApiMessage::Room(maze.start()))),
+            (Method::Post, url) if url.starts_with("/pathbot/rooms/") => {
+                let location_path = url.to_string();
+                let mut body = String::new();
+                let direction = request
+                    .as_reader()
+                    .read_to_string(&mut body)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| {
+                        serde_json::from_str::<MoveRequest>(&body).map_err(|e| e.to_string())
+                    });
+                match direction {
+                    Ok(MoveRequest { direction }) => {
+                        match maze.move_from(&location_path, direction) {
+                            Ok(answer) => (200, Some(answer)),
+                            Err(e @ MazeError::UnknownRoom(_)) => (404, Some(message(e))),
+                            Err(e @ MazeError::NoExit(_)) => (400, Some(message(e))),
+                        }
+                    }
+                    Err(e) => (400, Some(message(format!("Invalid move request: {}", e)))),
+                }
+            }
+            _ => (404, Some(message("Not found."))),
+        };
+        info!("{} {} -> {}", request.method(), request.url(), status);
+
+        let body = answer
+            .map(|answer| serde_json::to_string(&answer).expect("Cannot serialize the answer."))
+            .unwrap_or_default();
+        let response = Response::from_data(body.into_bytes())
+            .with_status_code(status)
+            .with_header(header("Content-Type", "application/json"))
+            .with_header(header("Access-Control-Allow-Origin", "*"))
+            .with_header(header("Access-Control-Allow-Methods", "POST, OPTIONS"))
+            .with_header(header("Access-Control-Allow-Headers", "Content-Type"));
+        if let Err(e) = request.respond(response) {
+            warn!("Cannot send the response: {}", e);
+        }
+    }
+
+    fn message(message: impl ToString) -> PathbotApiMessage {
+        PathbotApiMessage::Message(Message {
+            message: message.to_string(),
+            extra: Extra::new(),
+        })
+    }
+
+    fn header(name: &str, value: &str) -> Header {
+        Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("Invalid header.")
+    }
+}
@@ -1,6 +1,6 @@
 use yew::{html, Component, ComponentLink, Html, Renderable, ShouldRender};
 
-use crate::MazeExitHint;
+use crate::{core::Coordinate, CompassDirection, MazeExitHint};
 
 pub struct Compass {
     props: Props,
@@ -12,6 +12,8 @@ pub enum Msg {}
 pub struct Props {
     pub maze_exit_hint: Option<MazeExitHint>,
     pub exited: bool,
+    /// The most likely place of the exit, relative to the current room.
+    pub exit_guess: Option<Coordinate>,
 }
 
 impl Default for Props {
@@ -19,6 +21,7 @@ impl Default for Props {
         Props {
             maze_exit_hint: None,
             exited: false,
+            exit_guess: None,
         }
     }
 }
@@ -65,13 +68,66 @@ impl Renderable<Compass> for Compass {
             .unwrap_or("?".to_string());
         html! {
             <div class="compass" style=compass_style>
-                <img src="compass.png" style=rotate_style />
+                <div style="position: relative;">
+                    <img src="compass.png" style=rotate_style />
+                    { self.view_guess_marker() }
+                </div>
                 <p>
                     { "Direction: "}{ direction }
                     <br />
                     { "Distance: "}{ distance_str }
+                    <br />
+                    { "Best guess: "}{ self.guess_str() }
                 </p>
             </div>
         }
     }
 }
+
+impl Compass {
+    /// A dot on the rim of the compass, towards the best guess.
+    fn view_guess_marker(&self) -> Html<Self> {
+        let guess = match (self.props.exit_guess, self.props.exited) {
+            (Some(guess), false) => guess,
+            _ => return html! {},
+        };
+        let angle = (guess.x as f64).atan2(-guess.y as f64).to_degrees();
+        let marker_style = format!(
+            "position: absolute; \
+             left: 0; \
+             top: 0; \
+             width: 100px; \
+             height: 100px; \
+             transform: rotate({}deg);",
+            angle
+        );
+        html! {
+            <div style=marker_style title="Best guess">
+                <span style="position: absolute; left: 44px; top: -6px; color: green;">
+                    { "●" }
+                </span>
+            </div>
+        }
+    }
+
+    fn guess_str(&self) -> String {
+        let guess = match (self.props.exit_guess, self.props.exited) {
+            (Some(guess), false) => guess,
+            (_, true) => return "-".to_string(),
+            (None, false) => return "?".to_string(),
+        };
+        let steps = |delta: i32, positive, negative| match delta {
+            0 => None,
+            d if d > 0 => Some(format!("{} {}", d, positive)),
+            d => Some(format!("{} {}", -d, negative)),
+        };
+        let parts: Vec<_> = vec![
+            steps(-guess.y, CompassDirection::N.long_name(), CompassDirection::S.long_name()),
+            steps(guess.x, CompassDirection::E.long_name(), CompassDirection::W.long_name()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        parts.join(", ")
+    }
+}
@@ -0,0 +1,220 @@
+//! Runtime configuration
+//!
+//! Loaded from `config.toml`, served next to index.html. The URL query can
+//! override it: `?profile=<name>` selects a profile, and `?api=<base url>`
+//! adds and selects a "custom" profile.
+//!
+//! A profile is either a Pathbot API server (`base_url`), a maze generated
+//! in the browser (`width`, `height` and `seed`), or a recorded session
+//! played again (`recording`, the URL of the recording).
+use serde::Deserialize;
+use std::fmt;
+
+use crate::maze::{Maze, MAX_ROOMS};
+use crate::retry::RetryPolicy;
+
+pub const CONFIG_URL: &str = "config.toml";
+
+const CUSTOM_PROFILE: &str = "custom";
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+pub struct Config {
+    pub default_profile: String,
+    pub profiles: Vec<Profile>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+/// A named API endpoint.
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    #[serde(flatten)]
+    pub backend: Backend,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(untagged)]
+pub enum Backend {
+    Http { base_url: String },
+    Memory { width: u32, height: u32, seed: u64 },
+    Replay { recording: String },
+}
+
+/// A configuration that can't be used.
+#[derive(PartialEq, Debug, Clone)]
+pub enum ConfigError {
+    /// The maze generated in the browser would have no room.
+    EmptyMaze { profile: String },
+    /// The maze generated in the browser would have more than `MAX_ROOMS`
+    /// rooms.
+    HugeMaze { profile: String },
+    /// The URL query selects a profile that isn't configured.
+    UnknownProfile { profile: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::EmptyMaze { profile } => write!(
+                f,
+                "the maze of the {} profile has no room, its width and height must be \
+                 at least 1",
+                profile
+            ),
+            ConfigError::HugeMaze { profile } => write!(
+                f,
+                "the maze of the {} profile is too big, it can have at most {} rooms",
+                profile, MAX_ROOMS
+            ),
+            ConfigError::UnknownProfile { profile } => {
+                write!(f, "there is no profile named {}", profile)
+            }
+        }
+    }
+}
+
+/// Where the maze comes from: the base URL of the API, or what stands for it.
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Backend::Http { base_url } => write!(f, "{}", base_url),
+            Backend::Memory {
+                width,
+                height,
+                seed,
+            } => write!(f, "in-memory maze {}x{}, seed {}", width, height, seed),
+            Backend::Replay { recording } => write!(f, "replay of {}", recording),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            default_profile: "production".to_string(),
+            profiles: vec![
+                Profile {
+                    name: "production".to_string(),
+                    backend: Backend::Http {
+                        base_url: "https://api.noopschallenge.com".to_string(),
+                    },
+                },
+                Profile {
+                    name: "local mock".to_string(),
+                    backend: Backend::Http {
+                        base_url: "http://localhost:8000".to_string(),
+                    },
+                },
+                Profile {
+                    name: "in-memory maze".to_string(),
+                    backend: Backend::Memory {
+                        width: 10,
+                        height: 10,
+                        seed: 42,
+                    },
+                },
+                Profile {
+                    name: "recorded replay".to_string(),
+                    backend: Backend::Replay {
+                        recording: "recording.json".to_string(),
+                    },
+                },
+            ],
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Returns the default profile, or the first one if it doesn't exist.
+    pub fn default_profile(&self) -> Option<&Profile> {
+        self.profile(&self.default_profile)
+            .or_else(|| self.profiles.first())
+    }
+
+    /// Rejects the profiles that would fail once selected.
+    pub fn check(&self) -> Result<(), ConfigError> {
+        for profile in &self.profiles {
+            if let Backend::Memory { width, height, .. } = profile.backend {
+                if width == 0 || height == 0 {
+                    return Err(ConfigError::EmptyMaze {
+                        profile: profile.name.clone(),
+                    });
+                }
+                if !Maze::valid_size(width, height) {
+                    return Err(ConfigError::HugeMaze {
+                        profile: profile.name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the overrides of an URL query string, e.g. `?profile=local`.
+    ///
+    /// An unknown profile is reported, and the default one is kept.
+    pub fn apply_query(&mut self, query: &str) -> Result<(), ConfigError> {
+        let query = query.trim_start_matches('?');
+        let mut selected = None;
+        for (key, value) in query.split('&').filter_map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            Some((parts.next()?, percent_decode(parts.next()?)))
+        }) {
+            match key {
+                "profile" => selected = Some(value),
+                "api" => {
+                    self.profiles.retain(|p| p.name != CUSTOM_PROFILE);
+                    self.profiles.push(Profile {
+                        name: CUSTOM_PROFILE.to_string(),
+                        backend: Backend::Http { base_url: value },
+                    });
+                    selected = Some(CUSTOM_PROFILE.to_string());
+                }
+                _ => {}
+            }
+        }
+        match selected {
+            Some(profile) if self.profile(&profile).is_none() => {
+                Err(ConfigError::UnknownProfile { profile })
+            }
+            Some(profile) => {
+                self.default_profile = profile;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -0,0 +1,89 @@
+//! Room graph: which move leads from which room to which room
+//!
+//! Coordinates only say where a room is drawn, the graph says how rooms are
+//! connected.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::RoomId;
+use crate::pathbot_api::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Copy, Clone)]
+pub enum EdgeKind {
+    /// The move was made, and the server answered with this room.
+    Confirmed,
+    /// Deduced without making the move, e.g. the way back of a move.
+    Inferred,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Edge {
+    pub from: RoomId,
+    pub direction: MoveDirection,
+    pub to: RoomId,
+    pub kind: EdgeKind,
+}
+
+impl Edge {
+    /// The direction of the way back.
+    pub fn reverse_direction(&self) -> MoveDirection {
+        self.direction.opposite()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug, Clone)]
+pub struct RoomGraph {
+    edges: HashMap<RoomId, HashMap<MoveDirection, Edge>>,
+}
+
+impl RoomGraph {
+    pub fn clear(&mut self) {
+        self.edges.clear();
+    }
+
+    pub fn edge(&self, from: &str, direction: MoveDirection) -> Option<&Edge> {
+        self.edges.get(from)?.get(&direction)
+    }
+
+    /// Iterates over all the edges, in no particular order.
+    pub fn edges(&self) -> impl Iterator<Item = &Edge> {
+        self.edges.values().flat_map(|edges| edges.values())
+    }
+
+    pub fn edges_from<'a>(&'a self, from: &str) -> impl Iterator<Item = &'a Edge> {
+        self.edges.get(from).into_iter().flat_map(|edges| edges.values())
+    }
+
+    /// Records a move that was made.
+    pub fn confirm(&mut self, from: RoomId, direction: MoveDirection, to: RoomId) {
+        self.insert(Edge {
+            from,
+            direction,
+            to,
+            kind: EdgeKind::Confirmed,
+        });
+    }
+
+    /// Records a move that wasn't made. A confirmed edge is never replaced.
+    ///
+    /// Returns whether the edge was added.
+    pub fn infer(&mut self, from: RoomId, direction: MoveDirection, to: RoomId) -> bool {
+        if self.edge(&from, direction).is_some() {
+            return false;
+        }
+        self.insert(Edge {
+            from,
+            direction,
+            to,
+            kind: EdgeKind::Inferred,
+        });
+        true
+    }
+
+    fn insert(&mut self, edge: Edge) {
+        self.edges
+            .entry(edge.from.clone())
+            .or_default()
+            .insert(edge.direction, edge);
+    }
+}
@@ -0,0 +1,130 @@
+//! Navigation history: the rooms entered, to go back and forth in them
+//!
+//! Like in a browser, entering a room after going back forgets the rooms
+//! ahead. Going back and forth never needs the API: every room in the
+//! history is known, and a move only needs the location path of its room.
+use serde::{Deserialize, Serialize};
+
+use super::{RoomId, State, Status};
+use crate::pathbot_api::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Step {
+    pub room_id: RoomId,
+    /// The move that entered the room, if it was a move.
+    pub direction: Option<MoveDirection>,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug, Clone)]
+pub struct History {
+    steps: Vec<Step>,
+    /// The step of the current room.
+    position: usize,
+}
+
+impl History {
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.position > 0
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        self.position + 1 < self.steps.len()
+    }
+
+    pub fn current(&self) -> Option<&Step> {
+        self.steps.get(self.position)
+    }
+
+    /// The last `count` steps, up to the current room.
+    pub fn recent(&self, count: usize) -> &[Step] {
+        if self.steps.is_empty() {
+            return &[];
+        }
+        let end = self.position + 1;
+        &self.steps[end.saturating_sub(count)..end]
+    }
+
+    fn clear(&mut self) {
+        self.steps.clear();
+        self.position = 0;
+    }
+
+    /// Forgets the rooms ahead, if any.
+    fn push(&mut self, step: Step) {
+        if !self.steps.is_empty() {
+            self.steps.truncate(self.position + 1);
+        }
+        self.steps.push(step);
+        self.position = self.steps.len() - 1;
+    }
+}
+
+// History
+impl State {
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Records the entry in a room, from the room of the current step.
+    pub(super) fn record_step(&mut self, room_id: &RoomId) {
+        let from = match self.history.current() {
+            Some(step) if &step.room_id == room_id => return,
+            Some(step) => Some(&step.room_id),
+            None => None,
+        };
+        let direction = from.and_then(|from| {
+            self.graph
+                .edges_from(from)
+                .find(|edge| &edge.to == room_id)
+                .map(|edge| edge.direction)
+        });
+        self.history.push(Step {
+            room_id: room_id.clone(),
+            direction,
+        });
+    }
+
+    pub(super) fn clear_history(&mut self) {
+        self.history.clear();
+    }
+
+    /// Goes back to the previous room of the history, if any. There is no
+    /// way back out of the exit.
+    pub fn go_back(&mut self) -> Option<&RoomId> {
+        if self.at_exit() || !self.history.can_go_back() {
+            return None;
+        }
+        self.history.position -= 1;
+        self.enter_history_step()
+    }
+
+    /// Goes forward to the next room of the history, if any.
+    pub fn go_forward(&mut self) -> Option<&RoomId> {
+        if self.at_exit() || !self.history.can_go_forward() {
+            return None;
+        }
+        self.history.position += 1;
+        self.enter_history_step()
+    }
+
+    fn at_exit(&self) -> bool {
+        match self.current_room() {
+            Some(room) => room.status == RoomStatus::Finished,
+            None => self.exited(),
+        }
+    }
+
+    fn enter_history_step(&mut self) -> Option<&RoomId> {
+        let room_id = self.history.steps[self.history.position].room_id.clone();
+        self.status = Status::InRoom(room_id);
+        self.current_room_id()
+    }
+}
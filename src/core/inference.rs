@@ -0,0 +1,165 @@
+//! What the known rooms tell about their neighbours
+//!
+//! A room lists its exits, so each of its sides is an observed passage or
+//! wall. Its neighbours must agree: the side of an unknown neighbour is
+//! inferred from it, and a known neighbour that disagrees is a
+//! contradiction.
+use std::{collections::HashMap, fmt};
+
+use super::{Coordinate, RoomId, State};
+use crate::pathbot_api::*;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Knowledge {
+    /// Listed in the exits of the room.
+    ObservedPassage,
+    /// Not listed in the exits of the room.
+    ObservedWall,
+    /// Listed in the exits of the neighbour.
+    InferredPassage,
+    /// Not listed in the exits of the neighbour.
+    InferredWall,
+    Unknown,
+}
+
+impl Default for Knowledge {
+    fn default() -> Self {
+        Knowledge::Unknown
+    }
+}
+
+/// The four sides of a coordinate.
+#[derive(Default, PartialEq, Debug, Copy, Clone)]
+pub struct Sides {
+    north: Knowledge,
+    south: Knowledge,
+    east: Knowledge,
+    west: Knowledge,
+}
+
+impl Sides {
+    pub fn get(&self, direction: MoveDirection) -> Knowledge {
+        use MoveDirection::*;
+        match direction {
+            N => self.north,
+            S => self.south,
+            E => self.east,
+            W => self.west,
+        }
+    }
+
+    fn set(&mut self, direction: MoveDirection, knowledge: Knowledge) {
+        use MoveDirection::*;
+        match direction {
+            N => self.north = knowledge,
+            S => self.south = knowledge,
+            E => self.east = knowledge,
+            W => self.west = knowledge,
+        }
+    }
+
+    pub fn unknown(&self) -> Vec<MoveDirection> {
+        MoveDirection::ALL
+            .iter()
+            .cloned()
+            .filter(|direction| self.get(*direction) == Knowledge::Unknown)
+            .collect()
+    }
+}
+
+/// `from` has an exit to `to`, but `to` has no exit back.
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub struct Contradiction {
+    pub from: RoomId,
+    pub direction: MoveDirection,
+    pub to: RoomId,
+}
+
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct Inference {
+    cells: HashMap<Coordinate, Sides>,
+    contradictions: Vec<Contradiction>,
+}
+
+impl Inference {
+    /// Returns `None` for the coordinates that nothing is known about.
+    pub fn cell(&self, coordinate: Coordinate) -> Option<&Sides> {
+        self.cells.get(&coordinate)
+    }
+
+    /// Iterates over the known rooms and their neighbours.
+    pub fn cells(&self) -> impl Iterator<Item = (Coordinate, &Sides)> {
+        self.cells.iter().map(|(coordinate, sides)| (*coordinate, sides))
+    }
+
+    pub fn contradictions(&self) -> &[Contradiction] {
+        &self.contradictions
+    }
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} has an exit to the {}, but {} has no exit back",
+            self.from,
+            self.direction.long_name(),
+            self.to
+        )
+    }
+}
+
+// Inference
+impl State {
+    /// Derives the walls and passages of every coordinate next to a known
+    /// room. The coordinates with more than one room are left out: the maze
+    /// isn't a grid there.
+    pub fn infer(&self) -> Inference {
+        let mut inference = Inference::default();
+        let single_room = |coordinate| match self.rooms_at(coordinate) {
+            [id] => self.room(id).map(|room| (id, room)),
+            _ => None,
+        };
+
+        for (id, room, coordinate) in self.rooms() {
+            if self.rooms_at(coordinate).len() > 1 {
+                continue;
+            }
+            for &direction in MoveDirection::ALL.iter() {
+                let passage = room.exits.contains(&direction);
+                let observed = match passage {
+                    true => Knowledge::ObservedPassage,
+                    false => Knowledge::ObservedWall,
+                };
+                inference
+                    .cells
+                    .entry(coordinate)
+                    .or_default()
+                    .set(direction, observed);
+
+                let neighbour = coordinate + direction.delta();
+                if self.rooms_at(neighbour).is_empty() {
+                    let inferred = match passage {
+                        true => Knowledge::InferredPassage,
+                        false => Knowledge::InferredWall,
+                    };
+                    inference
+                        .cells
+                        .entry(neighbour)
+                        .or_default()
+                        .set(direction.opposite(), inferred);
+                } else if let Some((neighbour_id, neighbour_room)) = single_room(neighbour) {
+                    let way_back = neighbour_room.exits.contains(&direction.opposite());
+                    if passage && !way_back {
+                        inference.contradictions.push(Contradiction {
+                            from: id.clone(),
+                            direction,
+                            to: neighbour_id.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        inference
+    }
+}
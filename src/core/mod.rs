@@ -0,0 +1,443 @@
+//! Maze model and movement rules
+//!
+//! Nothing in here depends on the web front end, so that it can be compiled
+//! and tested natively, and reused from other front ends.
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Add,
+};
+
+use crate::pathbot_api::*;
+
+mod graph;
+mod history;
+mod inference;
+mod path;
+mod triangulation;
+
+pub use graph::{Edge, EdgeKind, RoomGraph};
+pub use history::{History, Step};
+pub use inference::{Contradiction, Inference, Knowledge, Sides};
+pub use triangulation::ExitCandidates;
+
+pub type RoomId = String;
+
+/// Serialized to be saved, see `save`. The caches and what is derived from
+/// the rooms are not saved, but rebuilt.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct State {
+    rooms: HashMap<RoomId, (Room, Coordinate)>,
+    #[serde(skip)]
+    room_coords: HashMap<RoomId, Coordinate>,
+    /// A maze that isn't a grid can have many rooms at the same coordinate,
+    /// in the order of their discovery.
+    #[serde(with = "coordinate_map")]
+    coord_to_id: HashMap<Coordinate, Vec<RoomId>>,
+    graph: RoomGraph,
+    #[serde(skip)]
+    exit_candidates: ExitCandidates,
+    /// The rooms entered, the others were only fetched.
+    visited: HashSet<RoomId>,
+    history: History,
+    status: Status,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State {
+            rooms: HashMap::default(),
+            room_coords: HashMap::default(),
+            coord_to_id: HashMap::default(),
+            graph: RoomGraph::default(),
+            exit_candidates: ExitCandidates::default(),
+            visited: HashSet::default(),
+            history: History::default(),
+            status: Status::Loading,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub struct Coordinate {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Add for Coordinate {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Coordinate {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+/// Where the maze isn't a grid, see `State::insert_room`.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Conflict {
+    /// Another room was already at the coordinate of the new room. Both
+    /// rooms are kept.
+    Overlap(RoomId),
+    /// The room was already known at another coordinate, and keeps it.
+    Elsewhere {
+        known_at: Coordinate,
+        reached_at: Coordinate,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub enum Status {
+    Loading,
+    InRoom(RoomId),
+    /// We store the received exit message.
+    Finished(Exit),
+}
+
+// Accessors
+impl State {
+    pub fn status(&self) -> &Status {
+        &self.status
+    }
+
+    pub fn loading(&self) -> bool {
+        self.status == Status::Loading
+    }
+
+    pub fn exited(&self) -> bool {
+        match &self.status {
+            Status::Finished(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn room(&self, id: &RoomId) -> Option<&Room> {
+        self.rooms.get(id).map(|t| &t.0)
+    }
+
+    pub fn room_coordinates(&self, id: &RoomId) -> Option<Coordinate> {
+        self.room_coords.get(id).cloned()
+    }
+
+    /// Returns the first room found at this coordinate.
+    pub fn room_at(&self, coordinate: Coordinate) -> Option<&RoomId> {
+        self.coord_to_id.get(&coordinate)?.first()
+    }
+
+    pub fn rooms_at(&self, coordinate: Coordinate) -> &[RoomId] {
+        self.coord_to_id
+            .get(&coordinate)
+            .map(|ids| ids.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns how many rooms were found at the coordinate of this room
+    /// before it.
+    pub fn room_layer(&self, id: &RoomId) -> usize {
+        self.room_coordinates(id)
+            .and_then(|coordinate| self.rooms_at(coordinate).iter().position(|i| i == id))
+            .unwrap_or(0)
+    }
+
+    /// Iterates over the coordinates with more than one room.
+    pub fn conflicts(&self) -> impl Iterator<Item = (Coordinate, &[RoomId])> {
+        self.coord_to_id
+            .iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|(coordinate, ids)| (*coordinate, ids.as_slice()))
+    }
+
+    pub fn graph(&self) -> &RoomGraph {
+        &self.graph
+    }
+
+    /// Whether the room was entered, and not only fetched ahead.
+    pub fn visited(&self, id: &RoomId) -> bool {
+        self.visited.contains(id)
+    }
+
+    /// Iterates over all the known rooms, in no particular order.
+    pub fn rooms(&self) -> impl Iterator<Item = (&RoomId, &Room, Coordinate)> {
+        self.rooms
+            .iter()
+            .map(|(id, (room, coordinate))| (id, room, *coordinate))
+    }
+
+    pub fn current_room_id(&self) -> Option<&RoomId> {
+        match &self.status {
+            Status::InRoom(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn current_room(&self) -> Option<&Room> {
+        self.current_room_id().and_then(|id| self.room(id))
+    }
+
+    pub fn current_exit_hint(&self) -> Option<MazeExitHint> {
+        self.current_room().map(|r| r.maze_exit_hint)
+    }
+
+    pub fn current_exits(&self) -> Option<&Vec<MoveDirection>> {
+        self.current_room().map(|r| &r.exits)
+    }
+
+    pub fn current_coordinates(&self) -> Option<Coordinate> {
+        self.current_room_id()
+            .and_then(|id| self.room_coordinates(id))
+    }
+
+    /// Returns the exits of a room that don't lead to a known room yet.
+    pub fn unexplored_exits(&self, id: &RoomId) -> Vec<MoveDirection> {
+        match self.room(id) {
+            Some(room) => room
+                .exits
+                .iter()
+                .cloned()
+                .filter(|exit| self.graph.edge(id, *exit).is_none())
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Iterates over the unexplored exits of all the rooms, in no particular
+    /// order.
+    pub fn frontier(&self) -> impl Iterator<Item = (&RoomId, MoveDirection)> {
+        self.rooms.keys().flat_map(move |id| {
+            self.unexplored_exits(id)
+                .into_iter()
+                .map(move |exit| (id, exit))
+        })
+    }
+}
+
+// Movement rules
+impl State {
+    pub fn can_move_direction(&self, direction: MoveDirection) -> bool {
+        if let Some(current_exits) = self.current_exits() {
+            current_exits.contains(&direction)
+        } else {
+            false
+        }
+    }
+
+    /// Returns the already known room in the given direction, if any.
+    ///
+    /// This follows the moves made, not the coordinates nor the inferred ways
+    /// back, which may not be right in a maze that isn't a grid.
+    pub fn known_destination(&self, direction: MoveDirection) -> Option<&RoomId> {
+        match self.graph.edge(self.current_room_id()?, direction)? {
+            edge if edge.kind == EdgeKind::Confirmed => Some(&edge.to),
+            _ => None,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.status = Status::Loading;
+        self.rooms.clear();
+        self.room_coords.clear();
+        self.coord_to_id.clear();
+        self.graph.clear();
+        self.exit_candidates = ExitCandidates::default();
+        self.visited.clear();
+        self.clear_history();
+    }
+
+    pub fn move_to(&mut self, room_id: RoomId) {
+        self.record_step(&room_id);
+        self.visited.insert(room_id.clone());
+        self.status = Status::InRoom(room_id);
+    }
+
+    /// Saves a room, reached from the current room by `last_move`.
+    ///
+    /// Returns the conflict with the known rooms, if any: the maze isn't a
+    /// grid there.
+    pub fn insert_room(
+        &mut self,
+        room: Room,
+        last_move: Option<MoveDirection>,
+    ) -> Option<Conflict> {
+        let from = last_move.map(|prev_move| (self.previous_room_id(), prev_move));
+        self.insert_room_from(room, from)
+    }
+
+    /// Saves a room, reached from a known room by a move, or the start room.
+    pub fn insert_room_from(
+        &mut self,
+        room: Room,
+        from: Option<(RoomId, MoveDirection)>,
+    ) -> Option<Conflict> {
+        // TODO: There are too many clone here
+        let location_path = room.location_path.clone();
+
+        // Save the room, with its position
+        let position = match from {
+            Some((prev_id, prev_move)) => {
+                let prev_position = self
+                    .rooms
+                    .get(&prev_id)
+                    .cloned()
+                    .expect("Logic error: room must exist.")
+                    .1;
+
+                // The way back is only known if the room has an exit there
+                if room.exits.contains(&prev_move.opposite()) {
+                    self.graph
+                        .infer(location_path.clone(), prev_move.opposite(), prev_id.clone());
+                }
+                self.graph.confirm(prev_id, prev_move, location_path.clone());
+
+                prev_position + prev_move.delta()
+            }
+            None => Coordinate { x: 0, y: 0 },
+        };
+
+        // A room reached again by another way keeps its position
+        if let Some((known_room, known_at)) = self.rooms.get_mut(&location_path) {
+            *known_room = room;
+            return match *known_at == position {
+                true => None,
+                false => Some(Conflict::Elsewhere {
+                    known_at: *known_at,
+                    reached_at: position,
+                }),
+            };
+        }
+        let hint = match room.status {
+            RoomStatus::InProgress => Some(room.maze_exit_hint),
+            RoomStatus::Finished => None,
+        };
+        self.rooms
+            .insert(room.location_path.clone(), (room, position));
+
+        // Add to the caches
+        self.room_coords.insert(location_path.clone(), position);
+        let rooms_here = self.coord_to_id.entry(position).or_default();
+        let conflict = rooms_here.first().cloned().map(Conflict::Overlap);
+        rooms_here.push(location_path);
+
+        if let Some(hint) = hint {
+            self.triangulate(position, hint);
+        }
+        conflict
+    }
+
+    pub fn reached_exit(&mut self, exit: Exit, last_move: Option<MoveDirection>) -> RoomId {
+        let last_move = last_move.expect("Logic error: we must have moved here.");
+        let exit_room_id = self.insert_exit(&exit, self.previous_room_id(), last_move);
+
+        self.status = Status::Finished(exit);
+
+        exit_room_id
+    }
+
+    /// Saves the exit, found from a known room by a move, without leaving
+    /// the current room.
+    pub fn insert_exit(&mut self, exit: &Exit, from: RoomId, direction: MoveDirection) -> RoomId {
+        let exit_move = direction.opposite();
+
+        // Fake a room (for the map)
+        // TODO: Shouldn't have to fake a room
+        let exit_room_id = "exit_room_yay".to_string();
+        let room = Room {
+            status: RoomStatus::Finished,
+            message: "Thank you for playing :)".to_string(),
+            exits: vec![exit_move],
+            description: exit.description.clone(),
+            maze_exit_hint: MazeExitHint {
+                // TODO: Should be None
+                direction: CompassDirection::N,
+                distance: 0,
+            },
+            // TODO: Should be None
+            location_path: exit_room_id.clone(),
+            extra: Extra::new(),
+        };
+        self.insert_room_from(room, Some((from, direction)));
+        if let Some(coordinate) = self.room_coordinates(&exit_room_id) {
+            self.exit_found(coordinate);
+        }
+        exit_room_id
+    }
+
+    /// Rebuilds what isn't saved, after deserializing.
+    pub(crate) fn rebuild(&mut self) {
+        self.room_coords = self
+            .rooms
+            .iter()
+            .map(|(id, (_, coordinate))| (id.clone(), *coordinate))
+            .collect();
+
+        self.exit_candidates = ExitCandidates::default();
+        let exit = self
+            .rooms()
+            .find(|(_, room, _)| room.status == RoomStatus::Finished)
+            .map(|(_, _, coordinate)| coordinate);
+        let largest_distance = self
+            .rooms()
+            .filter(|(_, room, _)| room.status == RoomStatus::InProgress)
+            .map(|(_, room, _)| room.maze_exit_hint.distance)
+            .max();
+        if let Some(distance) = largest_distance {
+            let margin = 2 * distance as i32 + 1;
+            self.search_exit(margin);
+            if self.exit_candidates.is_empty() && exit.is_none() {
+                self.search_exit_farther(margin);
+            }
+        }
+        if let Some(coordinate) = exit {
+            self.exit_found(coordinate);
+        }
+    }
+
+    fn previous_room_id(&self) -> RoomId {
+        match &self.status {
+            Status::InRoom(id) => id.clone(),
+            _ => panic!(
+                "Logic error: cannot insert room when status == {:?}.",
+                self.status
+            ),
+        }
+    }
+}
+
+impl MoveDirection {
+    pub fn delta(self) -> Coordinate {
+        match self {
+            MoveDirection::N => Coordinate { x: 0, y: -1 },
+            MoveDirection::S => Coordinate { x: 0, y: 1 },
+            MoveDirection::W => Coordinate { x: -1, y: 0 },
+            MoveDirection::E => Coordinate { x: 1, y: 0 },
+        }
+    }
+}
+
+/// JSON keys are strings: a map by coordinate is saved as a list of pairs.
+mod coordinate_map {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    use super::{Coordinate, RoomId};
+
+    pub fn serialize<S>(
+        map: &HashMap<Coordinate, Vec<RoomId>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let pairs: Vec<_> = map.iter().collect();
+        pairs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashMap<Coordinate, Vec<RoomId>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let pairs: Vec<(Coordinate, Vec<RoomId>)> = Vec::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
@@ -0,0 +1,108 @@
+//! Shortest paths between known rooms, and what is near a room
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{Edge, EdgeKind, RoomId, State};
+use crate::pathbot_api::*;
+
+// Paths
+impl State {
+    /// Returns the moves of a shortest path from the current room to `to`,
+    /// through the moves made. A way back that was only inferred may lead
+    /// elsewhere in a maze that isn't a grid.
+    pub fn path_to(&self, to: &RoomId) -> Option<Vec<MoveDirection>> {
+        self.path_between(self.current_room_id()?, to)
+    }
+
+    pub fn path_between(&self, from: &RoomId, to: &RoomId) -> Option<Vec<MoveDirection>> {
+        self.shortest_path(from, to, |edge| edge.kind == EdgeKind::Confirmed)
+    }
+
+    /// Like `path_to`, but also through the inferred ways back: a strategy or
+    /// a travel looks again after each move, so a wrong guess only costs a
+    /// move.
+    pub fn likely_path_to(&self, to: &RoomId) -> Option<Vec<MoveDirection>> {
+        self.shortest_path(self.current_room_id()?, to, |_| true)
+    }
+
+    fn shortest_path(
+        &self,
+        from: &RoomId,
+        to: &RoomId,
+        usable: impl Fn(&Edge) -> bool,
+    ) -> Option<Vec<MoveDirection>> {
+        // Breadth-first search, remembering how each room was reached
+        let mut reached_by: HashMap<&RoomId, (&RoomId, MoveDirection)> = HashMap::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(room_id) = queue.pop_front() {
+            if room_id == to {
+                break;
+            }
+            for edge in self.graph().edges_from(room_id).filter(|edge| usable(edge)) {
+                if &edge.to != from && !reached_by.contains_key(&edge.to) {
+                    reached_by.insert(&edge.to, (room_id, edge.direction));
+                    queue.push_back(&edge.to);
+                }
+            }
+        }
+
+        // Walk back from the destination
+        let mut path = Vec::new();
+        let mut room_id = to;
+        while room_id != from {
+            let (previous, direction) = reached_by.get(room_id)?;
+            path.push(*direction);
+            room_id = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Returns the rooms reached from the current room, the nearest first,
+    /// also through the inferred ways back: each with its distance, and the
+    /// first move of a shortest path to it, `None` for the current room.
+    pub fn nearest_rooms(
+        &self,
+    ) -> impl Iterator<Item = (&RoomId, usize, Option<MoveDirection>)> + '_ {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        if let Some(current) = self.current_room_id() {
+            seen.insert(current);
+            queue.push_back((current, 0, None));
+        }
+        std::iter::from_fn(move || {
+            let (room_id, distance, first_move) = queue.pop_front()?;
+            for edge in self.graph().edges_from(room_id) {
+                if seen.insert(&edge.to) {
+                    let first_move = first_move.or(Some(edge.direction));
+                    queue.push_back((&edge.to, distance + 1, first_move));
+                }
+            }
+            Some((room_id, distance, first_move))
+        })
+    }
+
+    /// Returns the unexplored exits of the rooms less than `depth` moves
+    /// away from `from`, the nearest first.
+    pub fn frontier_near(&self, from: &RoomId, depth: usize) -> Vec<(RoomId, MoveDirection)> {
+        let mut frontier = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(from);
+        queue.push_back((from, 0));
+        while let Some((room_id, distance)) = queue.pop_front() {
+            if distance >= depth {
+                continue;
+            }
+            for exit in self.unexplored_exits(room_id) {
+                frontier.push((room_id.clone(), exit));
+            }
+            for edge in self.graph().edges_from(room_id) {
+                if seen.insert(&edge.to) {
+                    queue.push_back((&edge.to, distance + 1));
+                }
+            }
+        }
+        frontier
+    }
+}
@@ -0,0 +1,204 @@
+//! Where the exit can be, according to the hints of the known rooms
+//!
+//! Each hint gives a bearing and a minimum distance from the coordinate of
+//! its room. The candidate cells are the ones agreeing with every hint. The
+//! distance being a lower bound only, the cells are searched around the
+//! rooms with a hint, up to twice the largest distance seen, and farther when no
+//! cell agrees anymore.
+use std::collections::HashMap;
+
+use super::{Coordinate, State};
+use crate::pathbot_api::*;
+
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct ExitCandidates {
+    /// The searched cells, from the corner with the smallest coordinates to
+    /// the opposite one.
+    area: Option<(Coordinate, Coordinate)>,
+    /// How far the cells were searched around the rooms with a hint, on each
+    /// axis.
+    margin: i32,
+    /// The candidate cells, with how many rooms farther than the hints say
+    /// they are, in total. The hints of a well-made maze are tight.
+    slack: HashMap<Coordinate, u32>,
+    best: Option<Coordinate>,
+}
+
+impl ExitCandidates {
+    pub fn contains(&self, coordinate: Coordinate) -> bool {
+        self.slack.contains_key(&coordinate)
+    }
+
+    /// Iterates over the candidate cells, in no particular order.
+    pub fn cells(&self) -> impl Iterator<Item = Coordinate> + '_ {
+        self.slack.keys().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.slack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slack.is_empty()
+    }
+
+    /// The most likely cell: the one that fits the hints most tightly.
+    pub fn best(&self) -> Option<Coordinate> {
+        self.best
+    }
+
+    /// Whether the searched cells include the ones around this cell.
+    fn covers(&self, cell: Coordinate, margin: i32) -> bool {
+        match self.area {
+            Some((min, max)) => {
+                cell.x - margin >= min.x
+                    && cell.y - margin >= min.y
+                    && cell.x + margin <= max.x
+                    && cell.y + margin <= max.y
+            }
+            None => false,
+        }
+    }
+
+    fn update_best(&mut self) {
+        self.best = self
+            .slack
+            .iter()
+            .min_by_key(|(coordinate, slack)| (**slack, coordinate.y, coordinate.x))
+            .map(|(coordinate, _)| *coordinate);
+    }
+}
+
+/// How many times the margin is doubled when no cell agrees with the hints
+/// anymore, before giving up.
+const MAX_WIDENINGS: usize = 2;
+
+/// The smallest area with all the cells, by its corners.
+fn bounding_box(cells: impl Iterator<Item = Coordinate>) -> Option<(Coordinate, Coordinate)> {
+    cells.fold(None, |area, cell| match area {
+        None => Some((cell, cell)),
+        Some((min, max)) => Some((
+            Coordinate {
+                x: min.x.min(cell.x),
+                y: min.y.min(cell.y),
+            },
+            Coordinate {
+                x: max.x.max(cell.x),
+                y: max.y.max(cell.y),
+            },
+        )),
+    })
+}
+
+/// Returns how many rooms farther than the hint the cell is, if it agrees
+/// with the hint.
+fn slack(position: Coordinate, hint: MazeExitHint, cell: Coordinate) -> Option<u32> {
+    let dx = i64::from(cell.x - position.x);
+    let dy = i64::from(cell.y - position.y);
+    let distance = (dx.abs() + dy.abs()) as u32;
+    if distance < hint.distance || CompassDirection::towards(dx, dy) != Some(hint.direction) {
+        return None;
+    }
+    Some(distance - hint.distance)
+}
+
+// Triangulation
+impl State {
+    pub fn exit_candidates(&self) -> &ExitCandidates {
+        &self.exit_candidates
+    }
+
+    /// Narrows down the candidates with the hint of a new room.
+    pub(super) fn triangulate(&mut self, position: Coordinate, hint: MazeExitHint) {
+        let margin = 2 * hint.distance as i32 + 1;
+        let candidates = &self.exit_candidates;
+        if margin > candidates.margin || !candidates.covers(position, candidates.margin) {
+            self.search_exit(margin.max(candidates.margin));
+            return;
+        }
+
+        let candidates = &mut self.exit_candidates;
+        let had_candidates = !candidates.is_empty();
+        candidates.slack.remove(&position);
+        candidates
+            .slack
+            .retain(|cell, total| match slack(position, hint, *cell) {
+                Some(slack) => {
+                    *total += slack;
+                    true
+                }
+                None => false,
+            });
+        if had_candidates && candidates.is_empty() {
+            let margin = candidates.margin;
+            self.search_exit_farther(margin);
+            return;
+        }
+        candidates.update_best();
+    }
+
+    /// Searches the cells again from scratch, `margin` cells around the rooms
+    /// with a hint.
+    pub(super) fn search_exit(&mut self, margin: i32) {
+        let hints: Vec<_> = self
+            .rooms()
+            .filter(|(_, room, _)| room.status == RoomStatus::InProgress)
+            .map(|(_, room, coordinate)| (coordinate, room.maze_exit_hint))
+            .collect();
+
+        let (min, max) = match bounding_box(hints.iter().map(|(position, _)| *position)) {
+            Some(area) => area,
+            None => return,
+        };
+        let min = Coordinate {
+            x: min.x - margin,
+            y: min.y - margin,
+        };
+        let max = Coordinate {
+            x: max.x + margin,
+            y: max.y + margin,
+        };
+        let mut candidates = ExitCandidates {
+            area: Some((min, max)),
+            margin,
+            ..ExitCandidates::default()
+        };
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                let cell = Coordinate { x, y };
+                if !self.rooms_at(cell).is_empty() {
+                    continue;
+                }
+                let total = hints
+                    .iter()
+                    .map(|(position, hint)| slack(*position, *hint, cell))
+                    .sum::<Option<u32>>();
+                if let Some(total) = total {
+                    candidates.slack.insert(cell, total);
+                }
+            }
+        }
+        candidates.update_best();
+        self.exit_candidates = candidates;
+    }
+
+    /// Searches with larger margins, until some cells agree with the hints:
+    /// the exit is farther than the searched cells.
+    pub(super) fn search_exit_farther(&mut self, mut margin: i32) {
+        for _ in 0..MAX_WIDENINGS {
+            margin *= 2;
+            self.search_exit(margin);
+            if !self.exit_candidates.is_empty() {
+                return;
+            }
+        }
+    }
+
+    /// The exit was found there.
+    pub(super) fn exit_found(&mut self, coordinate: Coordinate) {
+        let candidates = &mut self.exit_candidates;
+        candidates.slack.clear();
+        candidates.slack.insert(coordinate, 0);
+        candidates.best = Some(coordinate);
+    }
+}
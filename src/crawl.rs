@@ -0,0 +1,107 @@
+//! Exploration of the maze with many requests at once
+//!
+//! The API is stateless per location path: the unexplored exits of all the
+//! known rooms can be fetched in parallel, up to a concurrency limit. `Crawl`
+//! only keeps the books, the `Model` sends the requests and merges the rooms
+//! into the `State` as they arrive.
+//!
+//! The same books serve to crawl the whole maze, and to prefetch the rooms
+//! near the player.
+use std::collections::{HashMap, HashSet};
+
+use crate::core::{RoomId, State};
+use crate::pathbot_api::*;
+
+/// A move from a known room.
+pub type CrawlMove = (RoomId, MoveDirection);
+
+/// `T` is the task of a request: dropping it cancels the request.
+pub struct Crawl<T> {
+    concurrency: usize,
+    in_flight: HashMap<CrawlMove, Option<T>>,
+    /// The moves that failed, not tried again.
+    failed: HashSet<CrawlMove>,
+    started_ms: f64,
+    answers: u32,
+}
+
+impl<T> Crawl<T> {
+    pub fn new(concurrency: usize, now_ms: f64) -> Self {
+        Crawl {
+            concurrency: concurrency.max(1),
+            in_flight: HashMap::new(),
+            failed: HashSet::new(),
+            started_ms: now_ms,
+            answers: 0,
+        }
+    }
+
+    /// The moves to send now, staying under the concurrency limit.
+    pub fn next_moves(&self, state: &State) -> Vec<CrawlMove> {
+        let frontier = state
+            .frontier()
+            .map(|(id, direction)| (id.clone(), direction));
+        self.pick(frontier)
+    }
+
+    /// The moves to send now from the rooms less than `depth` moves away
+    /// from `from`, see `State::frontier_near`.
+    pub fn next_moves_near(&self, state: &State, from: &RoomId, depth: usize) -> Vec<CrawlMove> {
+        self.pick(state.frontier_near(from, depth).into_iter())
+    }
+
+    fn pick(&self, frontier: impl Iterator<Item = CrawlMove>) -> Vec<CrawlMove> {
+        let free = self.concurrency.saturating_sub(self.in_flight.len());
+        frontier
+            .filter(|crawl_move| {
+                !self.in_flight.contains_key(crawl_move) && !self.failed.contains(crawl_move)
+            })
+            .take(free)
+            .collect()
+    }
+
+    pub fn sent(&mut self, crawl_move: CrawlMove, task: Option<T>) {
+        self.in_flight.insert(crawl_move, task);
+    }
+
+    /// Returns whether the answer was awaited.
+    pub fn answered(&mut self, crawl_move: &CrawlMove, failed: bool) -> bool {
+        if self.in_flight.remove(crawl_move).is_none() {
+            return false;
+        }
+        self.answers += 1;
+        if failed {
+            self.failed.insert(crawl_move.clone());
+        }
+        true
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.failed.len()
+    }
+
+    /// Whether every reachable room is known, or couldn't be fetched.
+    pub fn done(&self, state: &State) -> bool {
+        self.in_flight.is_empty() && self.next_moves(state).is_empty()
+    }
+
+    /// The unexplored exits that were not tried, or are being tried.
+    pub fn open_frontier(&self, state: &State) -> usize {
+        state
+            .frontier()
+            .filter(|(id, direction)| !self.failed.contains(&((*id).clone(), *direction)))
+            .count()
+    }
+
+    pub fn requests_per_second(&self, now_ms: f64) -> f64 {
+        let elapsed_s = (now_ms - self.started_ms) / 1000.;
+        if elapsed_s <= 0. {
+            return 0.;
+        }
+        f64::from(self.answers) / elapsed_s
+    }
+}
@@ -0,0 +1,99 @@
+//! Errors while talking to the Pathbot API
+use std::{fmt, time::Duration};
+
+use crate::pathbot_api::{LocationPath, MoveDirection, PathbotApiMessage};
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum PathbotError {
+    /// The request couldn't be sent, or no answer was received. Browsers
+    /// report CORS failures like this too.
+    Network(String),
+    /// The server answered with an error status.
+    Http {
+        status: u16,
+        body: String,
+        /// Only the delay-seconds form of `Retry-After` is supported. It is
+        /// unreadable cross-origin, unless the server exposes it with
+        /// `Access-Control-Expose-Headers`.
+        retry_after: Option<Duration>,
+    },
+    /// The answer isn't a valid Pathbot message.
+    Deserialize { error: String, json: String },
+    /// The answer is a valid Pathbot message, but doesn't make sense here.
+    Protocol(String),
+    /// A replayed session never made this move.
+    NotInRecording {
+        location_path: LocationPath,
+        direction: MoveDirection,
+    },
+}
+
+impl PathbotError {
+    /// Explains the error to the player.
+    pub fn user_message(&self) -> String {
+        use PathbotError::*;
+        match self {
+            Network(_) => "Cannot reach the API. Are you offline, or is the server \
+                           refusing cross-origin requests (CORS)?"
+                .to_string(),
+            Http { status, body, .. } => match (status, api_message(body)) {
+                (_, Some(message)) => message,
+                (404, None) => "This room doesn't exist on the server.".to_string(),
+                (429, None) => "Too many requests, the API asks to slow down.".to_string(),
+                (400..=499, None) => format!("The API rejected the request (HTTP {}).", status),
+                (_, None) => format!("The API server failed (HTTP {}).", status),
+            },
+            Deserialize { .. } => "The API sent an answer that we don't understand.".to_string(),
+            Protocol(e) => format!("The API answered something unexpected: {}.", e),
+            NotInRecording { direction, .. } => format!(
+                "Going {} from this room is not in the recording.",
+                direction.long_name()
+            ),
+        }
+    }
+
+    /// Whether trying again later may work.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            PathbotError::Network(_) => true,
+            PathbotError::Http { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            PathbotError::Http { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PathbotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use PathbotError::*;
+        match self {
+            Network(e) => write!(f, "network error: {}", e),
+            Http { status, body, .. } => write!(f, "HTTP {}: {}", status, body),
+            Deserialize { error, json } => write!(f, "invalid answer ({}): {}", error, json),
+            Protocol(e) => write!(f, "protocol violation: {}", e),
+            NotInRecording {
+                location_path,
+                direction,
+            } => write!(
+                f,
+                "not in recording: {} from {}",
+                direction.short_name(),
+                location_path
+            ),
+        }
+    }
+}
+
+/// Error answers usually carry a Pathbot message, e.g. `{"message": "..."}`.
+fn api_message(body: &str) -> Option<String> {
+    match serde_json::from_str(body) {
+        Ok(PathbotApiMessage::Message(message)) => Some(message.message),
+        _ => None,
+    }
+}
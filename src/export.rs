@@ -0,0 +1,363 @@
+//! Export of the explored map, for offline analysis
+//!
+//! The known rooms, with their coordinates, exits and exit hints, and the
+//! passages between them, in three formats:
+//! - JSON, described by `docs/map.schema.json`,
+//! - Graphviz DOT, with the rooms pinned where the map draws them, and the
+//!   same colors: `neato -n2 -Tsvg pathbot-map.dot`,
+//! - GraphML, for tools like Gephi.
+//!
+//! Each export starts with the same `Metadata`.
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt::Write;
+
+use crate::config::Profile;
+use crate::core::{Coordinate, Edge, EdgeKind, RoomId, State};
+use crate::journal::iso_8601;
+use crate::map;
+use crate::pathbot_api::*;
+
+/// Bumped when the JSON export changes, with its schema.
+pub const VERSION: u32 = 1;
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum MapFormat {
+    Json,
+    Dot,
+    GraphMl,
+}
+
+impl MapFormat {
+    pub const ALL: [MapFormat; 3] = [MapFormat::Json, MapFormat::Dot, MapFormat::GraphMl];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MapFormat::Json => "JSON",
+            MapFormat::Dot => "DOT",
+            MapFormat::GraphMl => "GraphML",
+        }
+    }
+
+    pub fn filename(self) -> &'static str {
+        match self {
+            MapFormat::Json => "pathbot-map.json",
+            MapFormat::Dot => "pathbot-map.dot",
+            MapFormat::GraphMl => "pathbot-map.graphml",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            MapFormat::Json => "application/json",
+            MapFormat::Dot => "text/vnd.graphviz",
+            MapFormat::GraphMl => "application/graphml+xml",
+        }
+    }
+}
+
+/// Where the map comes from.
+#[derive(Serialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    pub profile: String,
+    /// The base URL of the API, or what stands for it.
+    pub api: String,
+    pub exported_at: String,
+    /// The first room entered.
+    pub start_room: Option<RoomId>,
+    pub exit: ExitStatus,
+}
+
+#[derive(Serialize, PartialEq, Debug, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum ExitStatus {
+    Unknown,
+    /// By a crawl, the player didn't take it yet.
+    Found,
+    Escaped,
+}
+
+impl ExitStatus {
+    fn name(self) -> &'static str {
+        match self {
+            ExitStatus::Unknown => "unknown",
+            ExitStatus::Found => "found",
+            ExitStatus::Escaped => "escaped",
+        }
+    }
+}
+
+impl Metadata {
+    pub fn new(profile: &Profile, state: &State, exported_ms: f64) -> Self {
+        let exit_found = state
+            .rooms()
+            .any(|(_, room, _)| room.status == RoomStatus::Finished);
+        let escaped = match state.current_room() {
+            Some(room) => room.status == RoomStatus::Finished,
+            None => state.exited(),
+        };
+        Metadata {
+            profile: profile.name.clone(),
+            api: profile.backend.to_string(),
+            exported_at: iso_8601(exported_ms),
+            start_room: state
+                .history()
+                .steps()
+                .first()
+                .map(|step| step.room_id.clone()),
+            exit: match (escaped, exit_found) {
+                (true, _) => ExitStatus::Escaped,
+                (false, true) => ExitStatus::Found,
+                (false, false) => ExitStatus::Unknown,
+            },
+        }
+    }
+}
+
+pub fn export(format: MapFormat, state: &State, metadata: &Metadata) -> String {
+    match format {
+        MapFormat::Json => {
+            serde_json::to_string_pretty(&to_json(state, metadata)).unwrap_or_default()
+        }
+        MapFormat::Dot => to_dot(state, metadata),
+        MapFormat::GraphMl => to_graphml(state, metadata),
+    }
+}
+
+pub fn to_json(state: &State, metadata: &Metadata) -> Value {
+    let room = |(id, room, coordinate): (&RoomId, &Room, Coordinate)| {
+        let exit_hint = match room.status {
+            RoomStatus::InProgress => json!({
+                "direction": room.maze_exit_hint.direction,
+                "distance": room.maze_exit_hint.distance,
+            }),
+            RoomStatus::Finished => Value::Null,
+        };
+        json!({
+            "id": id,
+            "x": coordinate.x,
+            "y": coordinate.y,
+            "layer": state.room_layer(id),
+            "status": room.status,
+            "visited": state.visited(id),
+            "description": room.description,
+            "exits": room.exits,
+            "exitHint": exit_hint,
+        })
+    };
+    let edge = |edge: &Edge| {
+        json!({
+            "from": edge.from,
+            "direction": edge.direction,
+            "to": edge.to,
+            "kind": kind_name(edge.kind),
+        })
+    };
+    json!({
+        "format": "pathbot-map",
+        "version": VERSION,
+        "metadata": metadata,
+        "rooms": sorted_rooms(state).into_iter().map(room).collect::<Vec<_>>(),
+        "edges": sorted_edges(state).into_iter().map(edge).collect::<Vec<_>>(),
+    })
+}
+
+pub fn to_dot(state: &State, metadata: &Metadata) -> String {
+    let mut dot = String::new();
+    let label = format!(
+        "{} ({}), exported on {}, exit {}",
+        metadata.profile,
+        metadata.api,
+        metadata.exported_at,
+        metadata.exit.name()
+    );
+    // Writing to a String cannot fail
+    writeln!(dot, "digraph pathbot {{").unwrap();
+    writeln!(dot, "    graph [label={}, labelloc=t];", dot_string(&label)).unwrap();
+    if let Some(start_room) = &metadata.start_room {
+        writeln!(dot, "    // Start room: {}", start_room).unwrap();
+    }
+    writeln!(
+        dot,
+        "    node [shape=box, style=filled, fixedsize=true, width={0:.3}, height={0:.3}, \
+         label=\"\"];",
+        map::ROOM_W / POINTS_PER_INCH
+    )
+    .unwrap();
+
+    for (id, room, coordinate) in sorted_rooms(state) {
+        let (x, y) = position(state, id, coordinate);
+        writeln!(
+            dot,
+            "    {} [pos=\"{},{}!\", fillcolor=\"{}\", tooltip={}];",
+            dot_string(id),
+            x,
+            y,
+            fill_color(state, id, room, coordinate),
+            dot_string(&room.description)
+        )
+        .unwrap();
+    }
+    for edge in sorted_edges(state) {
+        let style = match edge.kind {
+            EdgeKind::Confirmed => format!("color=\"{}\"", map::CONFIRMED_COLOR),
+            EdgeKind::Inferred => format!("color=\"{}\", style=dashed", map::INFERRED_COLOR),
+        };
+        writeln!(
+            dot,
+            "    {} -> {} [{}, tooltip=\"{}\"];",
+            dot_string(&edge.from),
+            dot_string(&edge.to),
+            style,
+            edge.direction.short_name()
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// The GraphML attributes: (id, for, type).
+const GRAPHML_KEYS: [(&str, &str, &str); 17] = [
+    ("profile", "graph", "string"),
+    ("api", "graph", "string"),
+    ("exportedAt", "graph", "string"),
+    ("startRoom", "graph", "string"),
+    ("exit", "graph", "string"),
+    ("label", "node", "string"),
+    ("x", "node", "double"),
+    ("y", "node", "double"),
+    ("layer", "node", "int"),
+    ("status", "node", "string"),
+    ("exits", "node", "string"),
+    ("hintDirection", "node", "string"),
+    ("hintDistance", "node", "int"),
+    ("visited", "node", "boolean"),
+    ("color", "node", "string"),
+    ("direction", "edge", "string"),
+    ("kind", "edge", "string"),
+];
+
+pub fn to_graphml(state: &State, metadata: &Metadata) -> String {
+    let mut xml = String::new();
+    let data = |xml: &mut String, indent: &str, key: &str, value: &str| {
+        writeln!(xml, "{}<data key=\"{}\">{}</data>", indent, key, xml_escape(value)).unwrap();
+    };
+
+    // Writing to a String cannot fail
+    writeln!(xml, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+    writeln!(xml, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">").unwrap();
+    for (id, domain, kind) in GRAPHML_KEYS.iter() {
+        writeln!(
+            xml,
+            "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"{2}\"/>",
+            id, domain, kind
+        )
+        .unwrap();
+    }
+    writeln!(xml, "  <graph id=\"pathbot\" edgedefault=\"directed\">").unwrap();
+    data(&mut xml, "    ", "profile", &metadata.profile);
+    data(&mut xml, "    ", "api", &metadata.api);
+    data(&mut xml, "    ", "exportedAt", &metadata.exported_at);
+    if let Some(start_room) = &metadata.start_room {
+        data(&mut xml, "    ", "startRoom", start_room);
+    }
+    data(&mut xml, "    ", "exit", metadata.exit.name());
+
+    for (id, room, coordinate) in sorted_rooms(state) {
+        let (x, y) = position(state, id, coordinate);
+        let exits: Vec<_> = room.exits.iter().map(|exit| exit.short_name()).collect();
+        writeln!(xml, "    <node id=\"{}\">", xml_escape(id)).unwrap();
+        data(&mut xml, "      ", "label", &room.description);
+        data(&mut xml, "      ", "x", &x.to_string());
+        data(&mut xml, "      ", "y", &y.to_string());
+        data(&mut xml, "      ", "layer", &state.room_layer(id).to_string());
+        data(&mut xml, "      ", "status", status_name(room.status));
+        data(&mut xml, "      ", "exits", &exits.join(" "));
+        if room.status == RoomStatus::InProgress {
+            let hint = room.maze_exit_hint;
+            data(&mut xml, "      ", "hintDirection", hint.direction.short_name());
+            data(&mut xml, "      ", "hintDistance", &hint.distance.to_string());
+        }
+        data(&mut xml, "      ", "visited", &state.visited(id).to_string());
+        data(&mut xml, "      ", "color", &fill_color(state, id, room, coordinate));
+        writeln!(xml, "    </node>").unwrap();
+    }
+    for edge in sorted_edges(state) {
+        writeln!(
+            xml,
+            "    <edge source=\"{}\" target=\"{}\">",
+            xml_escape(&edge.from),
+            xml_escape(&edge.to)
+        )
+        .unwrap();
+        data(&mut xml, "      ", "direction", edge.direction.short_name());
+        data(&mut xml, "      ", "kind", kind_name(edge.kind));
+        writeln!(xml, "    </edge>").unwrap();
+    }
+    writeln!(xml, "  </graph>").unwrap();
+    writeln!(xml, "</graphml>").unwrap();
+    xml
+}
+
+/// Graphviz positions are in points.
+const POINTS_PER_INCH: f64 = 72.;
+
+/// Rooms from the North-West, then the ones discovered later at the same
+/// coordinate, for the exports to be stable.
+fn sorted_rooms(state: &State) -> Vec<(&RoomId, &Room, Coordinate)> {
+    let mut rooms: Vec<_> = state.rooms().collect();
+    rooms.sort_by_key(|(id, _, coordinate)| (coordinate.y, coordinate.x, state.room_layer(id)));
+    rooms
+}
+
+fn sorted_edges(state: &State) -> Vec<&Edge> {
+    let mut edges: Vec<_> = state.graph().edges().collect();
+    edges.sort_by_key(|edge| (&edge.from, edge.direction.short_name()));
+    edges
+}
+
+/// Where the map draws the room, in points, the y axis pointing to the
+/// North.
+fn position(state: &State, id: &RoomId, coordinate: Coordinate) -> (f64, f64) {
+    let shift = state.room_layer(id) as f64 * map::LAYER_SHIFT;
+    let x = f64::from(coordinate.x) * (map::ROOM_W + map::EXIT_L) + shift;
+    let y = f64::from(-coordinate.y) * (map::ROOM_H + map::EXIT_L) - shift;
+    (x, y)
+}
+
+/// The color of the room on the map, by status, faded if not entered yet.
+fn fill_color(state: &State, id: &RoomId, room: &Room, coordinate: Coordinate) -> String {
+    let color = map::status_color(state, id, room, coordinate);
+    match state.visited(id) {
+        true => color.to_string(),
+        false => format!("{}{:02x}", color, (map::UNVISITED_ALPHA * 255.).round() as u8),
+    }
+}
+
+fn kind_name(kind: EdgeKind) -> &'static str {
+    match kind {
+        EdgeKind::Confirmed => "confirmed",
+        EdgeKind::Inferred => "inferred",
+    }
+}
+
+fn status_name(status: RoomStatus) -> &'static str {
+    match status {
+        RoomStatus::InProgress => "in-progress",
+        RoomStatus::Finished => "finished",
+    }
+}
+
+fn dot_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
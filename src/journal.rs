@@ -0,0 +1,231 @@
+//! Journal of the requests sent to the API, and of their raw answers
+//!
+//! It can be exported as a HAR 1.2 file, or as JSON Lines.
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+
+/// The oldest entries are forgotten past this size.
+const MAX_ENTRIES: usize = 1000;
+
+#[derive(Serialize, PartialEq, Debug, Clone)]
+pub struct JournalEntry {
+    /// Milliseconds since the Unix epoch.
+    pub started: f64,
+    pub request: RecordedRequest,
+    pub response: Option<RecordedResponse>,
+    /// Why no response was received.
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, PartialEq, Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Serialize, PartialEq, Debug, Clone)]
+pub struct RecordedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub latency_ms: f64,
+}
+
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct Journal {
+    entries: VecDeque<JournalEntry>,
+}
+
+impl Journal {
+    pub fn push(&mut self, entry: JournalEntry) {
+        if self.entries.len() >= MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates from the oldest to the newest entry.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &JournalEntry> {
+        self.entries.iter()
+    }
+
+    /// One JSON object per line.
+    pub fn to_json_lines(&self) -> String {
+        self.entries
+            .iter()
+            .filter_map(|entry| serde_json::to_string(entry).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// See http://www.softwareishard.com/blog/har-12-spec/
+    pub fn to_har(&self) -> Value {
+        json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": self.entries.iter().map(har_entry).collect::<Vec<_>>(),
+            }
+        })
+    }
+}
+
+impl JournalEntry {
+    pub fn new(request: RecordedRequest) -> Self {
+        JournalEntry {
+            started: now_ms(),
+            request,
+            response: None,
+            error: None,
+        }
+    }
+
+    pub fn received(&mut self, status: u16, headers: Vec<(String, String)>, body: String) {
+        self.response = Some(RecordedResponse {
+            status,
+            headers,
+            body,
+            latency_ms: now_ms() - self.started,
+        });
+    }
+
+    pub fn failed(&mut self, error: String) {
+        self.error = Some(error);
+    }
+}
+
+fn har_entry(entry: &JournalEntry) -> Value {
+    let headers = |headers: &[(String, String)]| {
+        headers
+            .iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect::<Vec<_>>()
+    };
+    let mime_type = |headers: &[(String, String)]| {
+        headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default()
+    };
+
+    let request = &entry.request;
+    let (time, response) = match &entry.response {
+        Some(response) => (
+            response.latency_ms,
+            json!({
+                "status": response.status,
+                "statusText": "",
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": headers(&response.headers),
+                "content": {
+                    "size": response.body.len(),
+                    "mimeType": mime_type(&response.headers),
+                    "text": response.body,
+                },
+                "redirectURL": "",
+                "headersSize": -1,
+                "bodySize": response.body.len(),
+            }),
+        ),
+        // HAR has no room for failed requests, they are status 0 by convention
+        None => (
+            0.,
+            json!({
+                "status": 0,
+                "statusText": entry.error.clone().unwrap_or_default(),
+                "httpVersion": "",
+                "cookies": [],
+                "headers": [],
+                "content": { "size": 0, "mimeType": "" },
+                "redirectURL": "",
+                "headersSize": -1,
+                "bodySize": -1,
+                "_error": entry.error,
+            }),
+        ),
+    };
+
+    json!({
+        "startedDateTime": iso_8601(entry.started),
+        "time": time,
+        "request": {
+            "method": request.method,
+            "url": request.url,
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers(&request.headers),
+            "queryString": [],
+            "postData": {
+                "mimeType": mime_type(&request.headers),
+                "text": request.body,
+            },
+            "headersSize": -1,
+            "bodySize": request.body.len(),
+        },
+        "response": response,
+        "cache": {},
+        "timings": { "send": 0, "wait": time, "receive": 0 },
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> f64 {
+    stdweb::web::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64() * 1000.)
+        .unwrap_or(0.)
+}
+
+/// Formats milliseconds since the Unix epoch, e.g. `2019-08-04T12:30:00.000Z`.
+pub fn iso_8601(ms: f64) -> String {
+    let ms = ms as i64;
+    let (days, ms_of_day) = (ms.div_euclid(86_400_000), ms.rem_euclid(86_400_000));
+
+    // Days to civil date, from http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        ms_of_day / 60_000 % 60,
+        ms_of_day / 1000 % 60,
+        ms_of_day % 1000
+    )
+}
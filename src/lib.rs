@@ -6,43 +6,129 @@ extern crate stdweb;
 use failure::Error;
 use linked_hash_map::LinkedHashMap;
 use log::*;
-use serde_json::json;
-use std::{collections::HashMap, ops::Add};
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
 use stdweb::{
-    traits::*,
     unstable::TryInto,
     web::{
-        document,
-        html_element::CanvasElement,
-        CanvasRenderingContext2d,
+        window,
         event::KeyDownEvent,
     },
-    traits::IKeyboardEvent,
+    traits::{IEvent, IKeyboardEvent, IMouseEvent},
 };
 use yew::{
-    format::{Json, Text},
+    format::{Nothing, Toml},
     html,
+    html::ChangeData,
     prelude::*,
     services::{
         fetch::{FetchService, FetchTask, Request, Response},
+        reader::{File, FileData, ReaderService, ReaderTask},
+        storage::{Area, StorageService},
+        timeout::{TimeoutService, TimeoutTask},
+        Task,
     }
 };
 
 mod components;
-mod pathbot_api;
+pub mod config;
+pub mod core;
+pub mod crawl;
+pub mod error;
+pub mod export;
+pub mod journal;
+mod map;
+pub mod maze;
+pub mod pathbot_api;
+pub mod recording;
+pub mod retry;
+pub mod save;
 mod services;
-
+pub mod strategy;
+pub mod transport;
+
+use crate::core::{Conflict, Contradiction, Coordinate, RoomId, State, Status, Step};
+use config::{Config, Profile};
+use crawl::Crawl;
+use error::PathbotError;
+use export::{MapFormat, Metadata};
+use map::MapMode;
+use journal::{iso_8601, now_ms, Journal, JournalEntry};
 use pathbot_api::*;
+use recording::Recording;
+use save::{Library, Save, SlotId};
 use services::{KeydownService, KeydownTask};
+use strategy::Strategy;
+use transport::{Answer, FetchRoomRequest, PathbotTransport};
 
 pub struct Model {
     state: State,
     link: ComponentLink<Model>,
 
+    config: Config,
+    profile: Option<Profile>,
+    config_task: Option<FetchTask>,
     fetch_service: FetchService,
+
+    transport: Option<Box<dyn PathbotTransport>>,
     fetching: bool,
     fetching_move: Option<MoveDirection>,
-    fetch_task: Option<FetchTask>,
+    fetch_task: Option<Box<dyn Task>>,
+    /// The request being sent, kept to retry it.
+    pending_request: Option<FetchRoomRequest>,
+    /// Number of retries of the pending request.
+    retries: u32,
+    timeout_service: TimeoutService,
+    retry_task: Option<TimeoutTask>,
+    /// Everything sent to and received from the API.
+    journal: Journal,
+    /// The unknown fields already received, see `pathbot_api::Extra`.
+    extra_keys: HashSet<String>,
+
+    map_mode: MapMode,
+    /// Already notified, see `State::infer`.
+    contradictions: HashSet<Contradiction>,
+
+    /// Moves pressed while fetching, run once the current move is done.
+    move_queue: VecDeque<MoveDirection>,
+    /// Remaining moves of a travel to a known room.
+    route: VecDeque<MoveDirection>,
+    travel_to: Option<RoomId>,
+    /// Where the move of the travel should lead: a way back that was only
+    /// inferred may lead elsewhere, and the travel is planned again.
+    travel_expected: Option<RoomId>,
+    travel_task: Option<TimeoutTask>,
+
+    /// Plays for the player when `autopilot` is on.
+    strategy: Box<dyn Strategy>,
+    autopilot: bool,
+    /// Delay between the moves of the autopilot.
+    autopilot_delay_ms: u64,
+    autopilot_task: Option<TimeoutTask>,
+
+    /// Explores the whole maze when on, see `crawl`.
+    crawl: Option<Crawl<Box<dyn Task>>>,
+    crawl_concurrency: usize,
+    /// Fetches ahead the rooms near the player, up to `lookahead_depth`
+    /// moves away. Off at 0.
+    prefetch: Crawl<Box<dyn Task>>,
+    lookahead_depth: usize,
+
+    storage: StorageService,
+    /// Of the state, see `save_state`.
+    save_task: Option<TimeoutTask>,
+    /// Already notified that the state can't be saved.
+    save_failed: bool,
+    /// Offered to resume on startup, see `save`.
+    previous_save: Option<Save>,
+    /// The named saves.
+    library: Library,
+    /// Of the next save in the library.
+    slot_name: String,
+    reader_service: ReaderService,
+    reader_task: Option<ReaderTask>,
 
     keydown_service: KeydownService,
     keydown_task: Option<KeydownTask>,
@@ -54,51 +140,24 @@ pub struct Model {
 
 type NotificationId = u32;
 
-#[derive(PartialEq, Debug, Clone)]
-pub struct State {
-    rooms: HashMap<RoomId, (Room, Coordinate)>,
-    room_coords: HashMap<RoomId, Coordinate>,
-    coord_to_id: HashMap<Coordinate, RoomId>,
-    status: Status,
-}
-
-impl Default for State {
-    fn default() -> Self {
-        State {
-            rooms: HashMap::default(),
-            room_coords: HashMap::default(),
-            coord_to_id: HashMap::default(),
-            status: Status::Loading,
-        }
-    }
-}
-
-type RoomId = String;
-
-#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
-pub struct Coordinate {
-    pub x: i32,
-    pub y: i32,
-}
-
-impl Add for Coordinate {
-    type Output = Self;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Coordinate {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
-    }
-}
-
-#[derive(PartialEq, Debug, Clone)]
-pub enum Status {
-    Loading,
-    InRoom(RoomId),
-    /// We store the received exit message.
-    Finished(Exit),
-}
+const MAX_QUEUED_MOVES: usize = 5;
+/// Delay between the moves of a travel, to see it on the map.
+const TRAVEL_STEP_MS: u64 = 150;
+/// Delays between the moves of the autopilot.
+const AUTOPILOT_SPEEDS: [(&str, u64); 4] = [
+    ("slow", 1000),
+    ("normal", 400),
+    ("fast", 100),
+    ("fastest", 0),
+];
+/// Concurrency limits of the crawl.
+const CRAWL_CONCURRENCIES: [usize; 5] = [1, 2, 4, 8, 16];
+const LOOKAHEAD_DEPTHS: [usize; 4] = [0, 1, 2, 3];
+/// Requests at once of the prefetch.
+const PREFETCH_CONCURRENCY: usize = 8;
+/// Delay before saving the rooms found in the background, to save the
+/// changes made meanwhile at once, e.g. by a crawl.
+const SAVE_DELAY_MS: u64 = 1000;
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct Notification {
@@ -114,26 +173,86 @@ pub enum NotificationLevel {
     Danger,
 }
 
+impl NotificationLevel {
+    /// How bad an API error is for the player.
+    fn of_error(error: &PathbotError) -> Self {
+        use PathbotError::*;
+        match error {
+            Http { status, .. } if *status < 500 => NotificationLevel::Warning,
+            NotInRecording { .. } => NotificationLevel::Warning,
+            Network(_) | Http { .. } | Deserialize { .. } | Protocol(_) => {
+                NotificationLevel::Danger
+            }
+        }
+    }
+}
+
 pub enum Msg {
     Init,
+    ConfigLoaded(Config),
+    SelectProfile(String),
     HandleKeyDown(KeyDownEvent),
     FetchNextRoom(MoveDirection),
+    ClearMoveQueue,
+    HistoryBack,
+    HistoryForward,
+    MapClicked(f64, f64),
+    TravelTo(RoomId),
+    TravelStep,
+    CancelTravel,
+    SelectStrategy(String),
+    SetAutopilotSpeed(u64),
+    AutopilotPlay,
+    AutopilotPause,
+    /// One move of the strategy.
+    AutopilotStep,
+    SetCrawlConcurrency(usize),
+    CrawlStart,
+    CrawlStop,
+    /// Contains the move from a known room.
+    CrawlAnswered(Answer, RoomId, MoveDirection),
+    SetLookaheadDepth(usize),
+    /// Contains the move from a known room.
+    Prefetched(Answer, RoomId, MoveDirection),
+    /// Contains the last move.
+    FetchAnswered(Answer, Option<MoveDirection>),
     /// Contains the last move.
     ReceivedRoom(Room, Option<MoveDirection>),
     MoveToRoom(RoomId),
     ReceivedMessage(Message),
     /// Contains the last move.
     ReceivedExit(Exit, Option<MoveDirection>),
-    FetchRoomFailed(Error),
+    FetchRoomFailed(PathbotError),
+    RetryFetch,
+    CancelFetch,
+    SelectMapMode(MapMode),
+    ExportMap(MapFormat),
+    ExportJournal(JournalFormat),
+    ClearJournal,
+    SaveState,
+    ResumeSave,
+    DiscardSave,
+    SlotNameChanged(String),
+    SaveToLibrary,
+    LoadSlot(SlotId),
+    RenameSlot(SlotId, String),
+    DuplicateSlot(SlotId),
+    DeleteSlot(SlotId),
+    ExportSlot(SlotId),
+    ImportSlot(File),
+    SlotFileRead(FileData),
     NewNotification(Notification),
     NotificationClosed(NotificationId),
     ClearNotifications,
     Noop,
 }
 
-enum FetchRoomRequest {
-    StartRoom,
-    NextRoom(LocationPath, MoveDirection),
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum JournalFormat {
+    Har,
+    JsonLines,
+    /// For the replay profiles, see `recording`.
+    Recording,
 }
 
 impl Component for Model {
@@ -146,10 +265,49 @@ impl Component for Model {
             state,
             link,
 
+            config: Config::default(),
+            profile: None,
+            config_task: None,
             fetch_service: FetchService::new(),
+
+            transport: None,
             fetching: false,
             fetching_move: None,
             fetch_task: None,
+            pending_request: None,
+            retries: 0,
+            timeout_service: TimeoutService::new(),
+            retry_task: None,
+            journal: Journal::default(),
+            extra_keys: HashSet::new(),
+
+            map_mode: MapMode::Status,
+            contradictions: HashSet::new(),
+
+            move_queue: VecDeque::with_capacity(MAX_QUEUED_MOVES),
+            route: VecDeque::new(),
+            travel_to: None,
+            travel_expected: None,
+            travel_task: None,
+
+            strategy: Box::new(strategy::DepthFirst::default()),
+            autopilot: false,
+            autopilot_delay_ms: AUTOPILOT_SPEEDS[1].1,
+            autopilot_task: None,
+
+            crawl: None,
+            crawl_concurrency: CRAWL_CONCURRENCIES[2],
+            prefetch: Crawl::new(PREFETCH_CONCURRENCY, 0.),
+            lookahead_depth: 0,
+
+            storage: StorageService::new(Area::Local),
+            save_task: None,
+            save_failed: false,
+            previous_save: None,
+            library: Library::default(),
+            slot_name: String::new(),
+            reader_service: ReaderService::new(),
+            reader_task: None,
 
             keydown_service: KeydownService::new(),
             keydown_task: None,
@@ -162,51 +320,349 @@ impl Component for Model {
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::Init => {
-                self.state.restart();
-                self.fetch(FetchRoomRequest::StartRoom);
+                self.load_previous_save();
+                self.load_library();
+                self.load_config();
 
                 let cb = self.link.send_back(|e| Msg::HandleKeyDown(e));
                 self.keydown_task = Some(self.keydown_service.spawn(cb));
             }
+            Msg::ConfigLoaded(mut config) => {
+                self.config_task = None;
+
+                let query = window().location().and_then(|l| l.search().ok());
+                if let Some(query) = query {
+                    if let Err(e) = config.apply_query(&query) {
+                        warn!("Invalid URL query: {}", e);
+                        self.link.send_self(Msg::NewNotification(Notification {
+                            message: format!(
+                                "The profile of the URL is ignored, {}: using the default one.",
+                                e
+                            ),
+                            level: NotificationLevel::Warning,
+                        }));
+                    }
+                }
+                if let Err(e) = config.check() {
+                    error!("Invalid configuration: {}", e);
+                    self.link.send_self(Msg::NewNotification(Notification {
+                        message: format!(
+                            "The configuration is invalid, using the default one: {}.",
+                            e
+                        ),
+                        level: NotificationLevel::Danger,
+                    }));
+                    config = Config::default();
+                }
+                self.config = config;
+
+                // Waits for the player to resume or not
+                if self.previous_save.is_some() {
+                    return true;
+                }
+                match self.config.default_profile().cloned() {
+                    Some(profile) => self.start_session(profile),
+                    None => self.link.send_self(Msg::NewNotification(Notification {
+                        message: "The configuration doesn't define any API profile.".to_string(),
+                        level: NotificationLevel::Danger,
+                    })),
+                }
+            }
+            Msg::SelectProfile(name) => {
+                let profile = self.config.profile(&name).cloned();
+                match profile {
+                    Some(profile) => self.start_session(profile),
+                    None => error!("Unknown profile: {}", name),
+                }
+            }
             Msg::HandleKeyDown(key) => {
                 use MoveDirection::*;
+                // The keys type in the form fields, e.g. the name of a save
+                if services::in_form_field(&key) {
+                    return false;
+                }
                 match key.key().as_ref() {
                     "N" | "n" => self.link.send_self(Msg::FetchNextRoom(N)),
                     "E" | "e" => self.link.send_self(Msg::FetchNextRoom(E)),
                     "W" | "w" => self.link.send_self(Msg::FetchNextRoom(W)),
                     "S" | "s" => self.link.send_self(Msg::FetchNextRoom(S)),
+                    "Escape" if self.autopilot => self.link.send_self(Msg::AutopilotPause),
+                    "Escape" if self.crawl.is_some() => self.link.send_self(Msg::CrawlStop),
+                    "Escape" if !self.route.is_empty() => {
+                        self.link.send_self(Msg::CancelTravel)
+                    }
+                    "Escape" if !self.move_queue.is_empty() => {
+                        self.link.send_self(Msg::ClearMoveQueue)
+                    }
                     "Escape" => self.link.send_self(Msg::ClearNotifications),
+                    "Backspace" => {
+                        // Not the back of the browser
+                        key.prevent_default();
+                        match key.shift_key() {
+                            true => self.link.send_self(Msg::HistoryForward),
+                            false => self.link.send_self(Msg::HistoryBack),
+                        }
+                    }
                     _ => {},
                 }
             }
             Msg::FetchNextRoom(direction) => {
-                if self.loading() || !self.state.can_move_direction(direction) {
+                self.stop_travel();
+                if self.loading() || !self.move_queue.is_empty() {
+                    return self.queue_move(direction);
+                }
+                if !self.state.can_move_direction(direction) {
+                    return false;
+                }
+                self.fetch_next_room(direction);
+            }
+            Msg::ClearMoveQueue => {
+                self.move_queue.clear();
+            }
+            Msg::HistoryBack => return self.navigate_history(State::go_back),
+            Msg::HistoryForward => return self.navigate_history(State::go_forward),
+            Msg::MapClicked(x, y) => match map::room_at_point(&self.state, x, y) {
+                Some(room_id) => self.link.send_self(Msg::TravelTo(room_id)),
+                None => return false,
+            },
+            Msg::TravelTo(room_id) => {
+                if self.loading() {
+                    return false;
+                }
+                if self.plan_travel(room_id) {
+                    self.move_queue.clear();
+                    self.link.send_self(Msg::TravelStep);
+                }
+            }
+            Msg::TravelStep => {
+                self.travel_task = None;
+                match self.route.pop_front() {
+                    Some(direction) if self.state.can_move_direction(direction) => {
+                        self.travel_expected = self
+                            .state
+                            .current_room_id()
+                            .and_then(|id| self.state.graph().edge(id, direction))
+                            .map(|edge| edge.to.clone());
+                        self.fetch_next_room(direction)
+                    }
+                    Some(direction) => {
+                        error!("Logic error: no exit to the {}.", direction.long_name());
+                        self.stop_travel();
+                    }
+                    None => {}
+                }
+            }
+            Msg::CancelTravel => {
+                info!("Travel cancelled.");
+                self.stop_travel();
+            }
+            Msg::SelectStrategy(name) => match strategy::by_name(&name) {
+                Some(strategy) => self.strategy = strategy,
+                None => error!("Unknown strategy: {}", name),
+            },
+            Msg::SetAutopilotSpeed(delay_ms) => {
+                self.autopilot_delay_ms = delay_ms;
+            }
+            Msg::AutopilotPlay => {
+                self.autopilot = true;
+                self.link.send_self(Msg::AutopilotStep);
+            }
+            Msg::AutopilotPause => {
+                self.pause_autopilot();
+            }
+            Msg::AutopilotStep => {
+                self.autopilot_task = None;
+                if self.loading() || self.state.exited() {
                     return false;
                 }
-                let status = self.state.status.clone();
-                match status {
-                    Status::Loading => error!("Logic error: no current room."),
-                    Status::InRoom(current_room_id) => {
-                        self.fetch(FetchRoomRequest::NextRoom(current_room_id, direction));
+                match self.strategy.next_move(&self.state) {
+                    Some(direction) if self.state.can_move_direction(direction) => {
+                        self.stop_travel();
+                        self.fetch_next_room(direction);
+                    }
+                    Some(direction) => {
+                        error!(
+                            "The {} strategy goes {} through a wall.",
+                            self.strategy.name(),
+                            direction.long_name()
+                        );
+                        self.pause_autopilot();
+                        self.link.send_self(Msg::NewNotification(Notification {
+                            message: format!(
+                                "The {} strategy wants to go {}, but there is no exit there. \
+                                 The autopilot is paused.",
+                                self.strategy.name(),
+                                direction.long_name()
+                            ),
+                            level: NotificationLevel::Warning,
+                        }));
+                    }
+                    None => {
+                        self.pause_autopilot();
+                        self.link.send_self(Msg::NewNotification(Notification {
+                            message: format!("The {} strategy is stuck.", self.strategy.name()),
+                            level: NotificationLevel::Info,
+                        }));
                     }
-                    Status::Finished(_) => error!("Logic error: no more room."),
                 }
             }
+            Msg::SetCrawlConcurrency(concurrency) => {
+                self.crawl_concurrency = concurrency;
+            }
+            Msg::CrawlStart => {
+                if self.state.loading() || self.state.exited() || self.crawl.is_some() {
+                    return false;
+                }
+                info!("Crawl, {} requests at once.", self.crawl_concurrency);
+                self.crawl = Some(Crawl::new(self.crawl_concurrency, now_ms()));
+                self.crawl_more();
+            }
+            Msg::CrawlStop => {
+                info!("Crawl stopped.");
+                self.stop_crawl();
+            }
+            Msg::CrawlAnswered(answer, from, direction) => {
+                let failed = crawl_failed(&answer);
+                self.journal.push(answer.entry);
+                let crawl_move = (from, direction);
+                let awaited = match &mut self.crawl {
+                    Some(crawl) => crawl.answered(&crawl_move, failed),
+                    None => false,
+                };
+                if !awaited {
+                    return true;
+                }
+                if let Ok(message) = &answer.result {
+                    self.check_extra_keys(message);
+                }
+
+                let (from, direction) = crawl_move;
+                match answer.result {
+                    Ok(PathbotApiMessage::Room(room)) => {
+                        self.save_room(room, Some((from, direction)));
+                        self.save_state();
+                        self.crawl_more();
+                    }
+                    Ok(PathbotApiMessage::Exit(exit)) => {
+                        self.state.insert_exit(&exit, from, direction);
+                        self.save_state();
+                        self.stop_crawl();
+                        self.link.send_self(Msg::NewNotification(Notification {
+                            message: "The crawl found the exit, travel there to escape!"
+                                .to_string(),
+                            level: NotificationLevel::Success,
+                        }));
+                    }
+                    Ok(PathbotApiMessage::Message(message)) => {
+                        let direction = direction.long_name();
+                        warn!("Crawl from {} to the {}: {}", from, direction, message.message);
+                        self.crawl_more();
+                    }
+                    Err(e) => {
+                        error!("Crawl from {} to the {}: {}", from, direction.long_name(), e);
+                        self.crawl_more();
+                    }
+                }
+                map::draw(&self.state, self.map_mode);
+            }
+            Msg::SetLookaheadDepth(depth) => {
+                info!("Lookahead depth: {}", depth);
+                self.lookahead_depth = depth;
+                self.stop_prefetch();
+                self.prefetch_near();
+            }
+            Msg::Prefetched(answer, from, direction) => {
+                let failed = crawl_failed(&answer);
+                self.journal.push(answer.entry);
+                let crawl_move = (from, direction);
+                if !self.prefetch.answered(&crawl_move, failed) {
+                    return true;
+                }
+                if let Ok(message) = &answer.result {
+                    self.check_extra_keys(message);
+                }
+
+                let (from, direction) = crawl_move;
+                match answer.result {
+                    Ok(PathbotApiMessage::Room(room)) => {
+                        self.save_room(room, Some((from, direction)));
+                        self.save_state();
+                    }
+                    Ok(PathbotApiMessage::Exit(exit)) => {
+                        info!("Prefetched the exit.");
+                        self.state.insert_exit(&exit, from, direction);
+                        self.save_state();
+                    }
+                    Ok(PathbotApiMessage::Message(message)) => {
+                        let direction = direction.long_name();
+                        debug!("Prefetch from {} to the {}: {}", from, direction, message.message);
+                    }
+                    Err(e) => {
+                        warn!("Prefetch from {} to the {}: {}", from, direction.long_name(), e);
+                    }
+                }
+                map::draw(&self.state, self.map_mode);
+                self.prefetch_near();
+            }
+            Msg::FetchAnswered(answer, last_move) => {
+                self.journal.push(answer.entry);
+                if let Ok(message) = &answer.result {
+                    self.check_extra_keys(message);
+                }
+                self.link.send_self(match answer.result {
+                    Ok(PathbotApiMessage::Room(room)) => Msg::ReceivedRoom(room, last_move),
+                    Ok(PathbotApiMessage::Message(message)) => Msg::ReceivedMessage(message),
+                    Ok(PathbotApiMessage::Exit(exit)) => Msg::ReceivedExit(exit, last_move),
+                    Err(e) => Msg::FetchRoomFailed(e),
+                });
+            }
             Msg::ReceivedRoom(room, last_move) => {
-                self.fetching = false;
-                self.fetching_move = None;
+                self.fetch_done();
 
                 let room_id = room.location_path.clone();
-                self.state.insert_room(room, last_move);
+                let from = match (self.state.current_room_id(), last_move) {
+                    (Some(current_room_id), Some(last_move)) => {
+                        Some((current_room_id.clone(), last_move))
+                    }
+                    _ => None,
+                };
+                self.save_room(room, from);
                 self.link.send_self(Msg::MoveToRoom(room_id));
             }
             Msg::MoveToRoom(room_id) => {
-                self.state.status = Status::InRoom(room_id);
-                self.state.draw_map();
+                self.state.move_to(room_id);
+                self.save_state_now();
+                map::draw(&self.state, self.map_mode);
+                self.prefetch_near();
+                self.run_queued_move();
+                if let Some(expected) = self.travel_expected.take() {
+                    if self.state.current_room_id() != Some(&expected) {
+                        warn!("The travel didn't reach {}, planning it again.", expected);
+                        let to = self.travel_to.clone();
+                        self.stop_travel();
+                        if let Some(to) = to {
+                            self.plan_travel(to);
+                        }
+                    }
+                }
+                if self.route.is_empty() {
+                    self.travel_to = None;
+                }
+                if !self.route.is_empty() && self.travel_task.is_none() {
+                    let delay = Duration::from_millis(TRAVEL_STEP_MS);
+                    let callback = self.link.send_back(|_| Msg::TravelStep);
+                    self.travel_task = Some(self.timeout_service.spawn(delay, callback));
+                } else if self.autopilot && self.move_queue.is_empty() && !self.fetching {
+                    let delay = Duration::from_millis(self.autopilot_delay_ms);
+                    let callback = self.link.send_back(|_| Msg::AutopilotStep);
+                    self.autopilot_task = Some(self.timeout_service.spawn(delay, callback));
+                }
             }
             Msg::ReceivedMessage(message) => {
-                self.fetching = false;
-                self.fetching_move = None;
+                self.fetch_done();
+                self.move_queue.clear();
+                self.stop_travel();
+                self.pause_autopilot();
 
                 self.link.send_self(Msg::NewNotification(Notification {
                     message: format!("{}", message.message),
@@ -214,8 +670,12 @@ impl Component for Model {
                 }));
             }
             Msg::ReceivedExit(exit, last_move) => {
-                self.fetching = false;
-                self.fetching_move = None;
+                self.fetch_done();
+                self.move_queue.clear();
+                self.stop_travel();
+                self.pause_autopilot();
+                self.stop_crawl();
+                self.stop_prefetch();
 
                 let room_id = self.state.reached_exit(exit, last_move);
                 self.link.send_self(Msg::MoveToRoom(room_id));
@@ -225,20 +685,200 @@ impl Component for Model {
                     level: NotificationLevel::Success,
                 }));
             }
-            Msg::FetchRoomFailed(response) => {
-                self.fetching = false;
-                self.fetching_move = None;
-                error!("Fetching room failed: {:?}", response);
+            Msg::FetchRoomFailed(error) => {
+                error!("Fetching room failed: {}", error);
+
+                let retry = self.retries + 1;
+                let random: f64 = js! { return Math.random(); }.try_into().unwrap_or(0.5);
+                if let Some(delay) = self.config.retry.delay(retry, &error, random) {
+                    info!("Retry {} in {:?}.", retry, delay);
+                    self.retries = retry;
+                    let callback = self.link.send_back(|_| Msg::RetryFetch);
+                    self.retry_task = Some(self.timeout_service.spawn(delay, callback));
+                    return true;
+                }
 
+                let retries = self.retries;
+                self.fetch_done();
+                self.move_queue.clear();
+                self.stop_travel();
+                self.pause_autopilot();
+                let message = match retries {
+                    0 => error.user_message(),
+                    _ => format!("{} (gave up after {} retries)", error.user_message(), retries),
+                };
                 self.link.send_self(Msg::NewNotification(Notification {
-                    message: format!(
-                        "An error occurred while communicating \
-                         with the API: {}",
-                        response
-                    ),
-                    level: NotificationLevel::Warning,
+                    message,
+                    level: NotificationLevel::of_error(&error),
                 }));
             }
+            Msg::RetryFetch => {
+                self.retry_task = None;
+                match self.pending_request.clone() {
+                    Some(request) => self.send(request),
+                    None => error!("Logic error: nothing to retry."),
+                }
+            }
+            Msg::CancelFetch => {
+                info!("Request cancelled.");
+                self.fetch_done();
+                self.move_queue.clear();
+                self.stop_travel();
+                self.pause_autopilot();
+            }
+            Msg::SelectMapMode(mode) => {
+                self.map_mode = mode;
+                if !self.state.loading() {
+                    map::draw(&self.state, mode);
+                }
+            }
+            Msg::ExportMap(format) => {
+                let profile = match &self.profile {
+                    Some(profile) if !self.state.loading() => profile,
+                    _ => return false,
+                };
+                let metadata = Metadata::new(profile, &self.state, now_ms());
+                let content = export::export(format, &self.state, &metadata);
+                services::download(format.filename(), format.mime_type(), &content);
+                return false;
+            }
+            Msg::ExportJournal(format) => {
+                let (filename, mime_type, content) = match format {
+                    JournalFormat::Har => (
+                        "pathbot.har",
+                        "application/json",
+                        self.journal.to_har().to_string(),
+                    ),
+                    JournalFormat::JsonLines => (
+                        "pathbot.jsonl",
+                        "application/x-ndjson",
+                        self.journal.to_json_lines(),
+                    ),
+                    JournalFormat::Recording => match Recording::from_journal(&self.journal) {
+                        Some(recording) => (
+                            "recording.json",
+                            "application/json",
+                            serde_json::to_string_pretty(&recording).unwrap_or_default(),
+                        ),
+                        None => {
+                            self.link.send_self(Msg::NewNotification(Notification {
+                                message: "Nothing to record, the maze wasn't started.".to_string(),
+                                level: NotificationLevel::Info,
+                            }));
+                            return false;
+                        }
+                    },
+                };
+                services::download(filename, mime_type, &content);
+                return false;
+            }
+            Msg::ClearJournal => {
+                self.journal.clear();
+            }
+            Msg::SaveState => {
+                self.save_state_now();
+                return false;
+            }
+            // Still offered if it can't be resumed
+            Msg::ResumeSave => match self.previous_save.clone() {
+                Some(save) => self.resume_save(save),
+                None => return false,
+            },
+            Msg::DiscardSave => {
+                self.previous_save = None;
+                self.storage.remove(save::STORAGE_KEY);
+                if let Some(profile) = self.config.default_profile().cloned() {
+                    self.start_session(profile);
+                }
+            }
+            Msg::SlotNameChanged(name) => {
+                self.slot_name = name;
+            }
+            Msg::SaveToLibrary => {
+                let profile = match &self.profile {
+                    Some(profile) if !self.state.loading() => profile.name.clone(),
+                    _ => return false,
+                };
+                let name = match self.slot_name.trim() {
+                    "" => format!("Maze {}", self.library.slots().len() + 1),
+                    name => name.to_string(),
+                };
+                let save = Save {
+                    profile,
+                    saved_ms: now_ms(),
+                    state: self.state.clone(),
+                };
+                let json = save::encode(&save.profile, &save.state, save.saved_ms);
+                self.add_to_library(&name, &save, &json);
+                self.slot_name.clear();
+            }
+            Msg::LoadSlot(id) => match self.restore_slot(id) {
+                Some(json) => match save::decode(&json) {
+                    Ok(save) => self.resume_save(save),
+                    Err(e) => self.link.send_self(Msg::NewNotification(Notification {
+                        message: format!("This maze can't be loaded: {}.", e),
+                        level: NotificationLevel::Warning,
+                    })),
+                },
+                None => return false,
+            },
+            Msg::RenameSlot(id, name) => {
+                if self.library.rename(id, &name) {
+                    self.store_library();
+                }
+            }
+            Msg::DuplicateSlot(id) => {
+                let json = match self.restore_slot(id) {
+                    Some(json) => json,
+                    None => return false,
+                };
+                if let Some(copy_id) = self.library.duplicate(id) {
+                    self.store_new_slot(copy_id, &json);
+                }
+            }
+            Msg::DeleteSlot(id) => {
+                if self.library.remove(id).is_some() {
+                    self.storage.remove(&save::slot_key(id));
+                    self.store_library();
+                }
+            }
+            Msg::ExportSlot(id) => {
+                let name = match self.library.slot(id) {
+                    Some(slot) => slot.name.clone(),
+                    None => return false,
+                };
+                if let Some(json) = self.restore_slot(id) {
+                    services::download(&format!("{}.json", name), "application/json", &json);
+                }
+                return false;
+            }
+            Msg::ImportSlot(file) => {
+                let callback = self.link.send_back(Msg::SlotFileRead);
+                self.reader_task = Some(self.reader_service.read_file(file, callback));
+                return false;
+            }
+            Msg::SlotFileRead(file) => {
+                self.reader_task = None;
+                let save = String::from_utf8(file.content)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| {
+                        let save = save::decode(&json).map_err(|e| e.to_string())?;
+                        Ok((json, save))
+                    });
+                match save {
+                    Ok((json, save)) => {
+                        let name = save::slot_name_from_file(&file.name);
+                        self.add_to_library(name, &save, &json);
+                    }
+                    Err(e) => {
+                        warn!("Invalid slot file {}: {}", file.name, e);
+                        self.link.send_self(Msg::NewNotification(Notification {
+                            message: format!("{} can't be imported: {}.", file.name, e),
+                            level: NotificationLevel::Warning,
+                        }));
+                    }
+                }
+            }
             Msg::NewNotification(notification) => {
                 let id = self.next_notification_id;
                 self.next_notification_id += 1;
@@ -263,13 +903,23 @@ impl Renderable<Model> for Model {
     fn view(&self) -> Html<Self> {
         let exit_hint = self.state.current_exit_hint();
         let exited = self.state.exited();
+        let exit_guess = self.exit_guess();
         html! {
             <section>
                 { self.view_notifications() }
-                <components::Compass: maze_exit_hint=exit_hint exited=exited/>
+                { self.view_resume() }
+                { self.view_profiles() }
+                <components::Compass: maze_exit_hint=exit_hint exited=exited exit_guess=exit_guess/>
                 { self.view_room() }
+                { self.view_fetch_status() }
                 { self.view_buttons() }
+                { self.view_history() }
+                { self.view_move_queue() }
+                { self.view_autopilot() }
+                { self.view_crawl() }
                 { self.view_map() }
+                { self.view_library() }
+                { self.view_journal() }
             </section>
         }
     }
@@ -278,7 +928,7 @@ impl Renderable<Model> for Model {
 // Accessors
 impl Model {
     fn loading(&self) -> bool {
-        self.fetching || self.state.status == Status::Loading
+        self.fetching || self.state.loading()
     }
 }
 
@@ -302,6 +952,87 @@ impl Model {
         }
     }
 
+    fn view_resume(&self) -> Html<Model> {
+        let save = match &self.previous_save {
+            Some(save) => save,
+            None => return html! {},
+        };
+        let exit = match save.exit_found() {
+            true => ", exit found",
+            false => "",
+        };
+        // Its profile isn't known before the configuration
+        let resume = match self.config_task.is_none() {
+            true => html! {
+                <button class="btn btn--primary" style="margin-left: 5px;"
+                    onclick=|_| Msg::ResumeSave>
+                    { "Resume previous maze" }
+                </button>
+            },
+            false => html! {
+                <button class="btn btn--inverted" style="margin-left: 5px;">
+                    { "Resume previous maze" }
+                </button>
+            },
+        };
+        html! {
+            <div id="resume">
+                { format!(
+                    "A maze explored with the {} API was saved on {}: {} rooms{}.",
+                    save.profile,
+                    iso_8601(save.saved_ms),
+                    save.rooms(),
+                    exit
+                ) }
+                { resume }
+                <button class="btn btn--inverted" style="margin-left: 5px;"
+                    onclick=|_| Msg::DiscardSave>
+                    { "New maze" }
+                </button>
+            </div>
+        }
+    }
+
+    fn view_profiles(&self) -> Html<Model> {
+        let active = self.profile.as_ref().map(|p| p.name.as_str());
+        let view_option = |profile: &Profile| {
+            let name = profile.name.clone();
+            let selected = Some(profile.name.as_str()) == active;
+            html! {
+                <option value=name selected=selected>{ &profile.name }</option>
+            }
+        };
+        html! {
+            <div id="profiles">
+                { "API: " }
+                <select onchange=|data| Model::profile_changed(data)>
+                    { for self.config.profiles.iter().map(view_option) }
+                </select>
+            </div>
+        }
+    }
+
+    fn profile_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Select(select) => select
+                .value()
+                .map(Msg::SelectProfile)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn map_mode_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Select(select) => select
+                .value()
+                .and_then(|name| MapMode::from_name(&name))
+                .map(Msg::SelectMapMode)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
     fn view_room(&self) -> Html<Model> {
         let status_to_str = |status| match status {
             RoomStatus::InProgress => "In progress",
@@ -315,15 +1046,16 @@ impl Model {
                 </span>
             }
         };
-        match &self.state.status {
+        match self.state.status() {
             Status::Loading => html! { <h1>{ "Loading first room..." }</h1> },
             Status::InRoom(room_id) => {
-                if let Some((room, _coord)) = self.state.rooms.get(room_id) {
+                if let Some(room) = self.state.room(room_id) {
                     html! {
                         <div>
                             <p id="status">{ status_to_str(room.status) }</p>
                             <p id="message">{ &room.message }</p>
                             <p id="description">{ &room.description }</p>
+                            { self.view_traits(room) }
                             <p id="exits">
                                 { "This room has " }
                                 { format!("{}", room.exits.len()) }
@@ -338,6 +1070,7 @@ impl Model {
                             <p id="question-action">
                                 { "What do you want to do?" }
                             </p>
+                            { self.view_extra(&room.extra) }
                         </div>
                     }
                 } else {
@@ -350,11 +1083,59 @@ impl Model {
                 <div>
                     <p id="status">{ status_to_str(exit.status) }</p>
                     <p id="description">{ &exit.description }</p>
+                    { self.view_extra(&exit.extra) }
                 </div>
             },
         }
     }
 
+    /// Flags the descriptions that don't agree with their room.
+    fn view_traits(&self, room: &Room) -> Html<Model> {
+        let mismatches = match room.traits() {
+            Some(traits) => traits.mismatches(room),
+            None => vec![],
+        };
+        html! {
+            <ul id="trait-mismatches">
+                { for mismatches.iter().map(|mismatch| html! {
+                    <li class="text-warning">{ format!("Odd room: {}.", mismatch) }</li>
+                }) }
+            </ul>
+        }
+    }
+
+    fn view_extra(&self, extra: &Extra) -> Html<Model> {
+        if extra.is_empty() {
+            return html! { <div></div> };
+        }
+        let json = serde_json::to_string_pretty(extra).unwrap_or_default();
+        html! {
+            <details id="raw-details">
+                <summary>{ "Raw details" }</summary>
+                <pre>{ json }</pre>
+            </details>
+        }
+    }
+
+    fn view_fetch_status(&self) -> Html<Model> {
+        if !self.fetching {
+            return html! { <p id="fetch-status"></p> };
+        }
+        let status = match self.retries {
+            0 => "Loading…".to_string(),
+            retry => format!("Retrying ({}/{})…", retry, self.config.retry.max_retries),
+        };
+        html! {
+            <p id="fetch-status">
+                { status }
+                <button class="btn btn--inverted" style="margin-left: 5px;"
+                    onclick=|_| Msg::CancelFetch>
+                    { "Cancel" }
+                </button>
+            </p>
+        }
+    }
+
     fn view_buttons(&self) -> Html<Model> {
         let loading = self.loading();
 
@@ -387,300 +1168,992 @@ impl Model {
         }
     }
 
+    fn view_history(&self) -> Html<Model> {
+        let history = self.state.history();
+        let loading = self.loading();
+        let button = |label: &str, enabled: bool, msg: fn() -> Msg| match enabled && !loading {
+            true => html! {
+                <button class="btn btn--primary" style="margin-left: 5px;"
+                    onclick=|_| msg()>
+                    { label }
+                </button>
+            },
+            false => html! {
+                <button class="btn btn--inverted" style="margin-left: 5px;">
+                    { label }
+                </button>
+            },
+        };
+        let view_step = |step: &Step| {
+            let coordinate = self
+                .state
+                .room_coordinates(&step.room_id)
+                .map(|c| format!("({}, {})", c.x, c.y))
+                .unwrap_or_default();
+            let arrow = match step.direction {
+                Some(MoveDirection::N) => "↑",
+                Some(MoveDirection::S) => "↓",
+                Some(MoveDirection::E) => "→",
+                Some(MoveDirection::W) => "←",
+                None => "•",
+            };
+            html! {
+                <span class="breadcrumb" style="margin-left: 5px;">
+                    { arrow }{ " " }{ coordinate }
+                </span>
+            }
+        };
+        let back = button("Back (Backspace)", history.can_go_back(), || Msg::HistoryBack);
+        let forward = button(
+            "Forward (Shift+Backspace)",
+            history.can_go_forward(),
+            || Msg::HistoryForward,
+        );
+        html! {
+            <div id="history">
+                { back }
+                { forward }
+                { for history.recent(map::HISTORY_SHOWN).iter().map(view_step) }
+            </div>
+        }
+    }
+
+    fn view_autopilot(&self) -> Html<Model> {
+        let active = self.strategy.name();
+        let view_strategy = |name: &&str| {
+            html! {
+                <option value=name selected=*name == active>{ name }</option>
+            }
+        };
+        let view_speed = |(name, delay_ms): &(&str, u64)| {
+            html! {
+                <option value=delay_ms.to_string() selected=*delay_ms == self.autopilot_delay_ms>
+                    { name }
+                </option>
+            }
+        };
+        let play_pause = match self.autopilot {
+            true => html! {
+                <button class="btn btn--primary" style="margin-left: 5px;"
+                    onclick=|_| Msg::AutopilotPause>
+                    { "Pause (Esc)" }
+                </button>
+            },
+            false => html! {
+                <button class="btn btn--primary" style="margin-left: 5px;"
+                    onclick=|_| Msg::AutopilotPlay>
+                    { "Play for me" }
+                </button>
+            },
+        };
+        html! {
+            <div id="autopilot">
+                <select onchange=|data| Model::strategy_changed(data)>
+                    { for strategy::NAMES.iter().map(view_strategy) }
+                </select>
+                <select onchange=|data| Model::speed_changed(data)>
+                    { for AUTOPILOT_SPEEDS.iter().map(view_speed) }
+                </select>
+                { play_pause }
+                <button class="btn btn--inverted" style="margin-left: 5px;"
+                    onclick=|_| Msg::AutopilotStep>
+                    { "Step" }
+                </button>
+                <ul id="autopilot-explanation">
+                    { for self.strategy.explain().into_iter().map(|line| html! { <li>{ line }</li> }) }
+                </ul>
+            </div>
+        }
+    }
+
+    fn view_crawl(&self) -> Html<Model> {
+        let view_concurrency = |concurrency: &usize| {
+            html! {
+                <option value=concurrency.to_string()
+                    selected=*concurrency == self.crawl_concurrency>
+                    { format!("{} at once", concurrency) }
+                </option>
+            }
+        };
+        let view_depth = |depth: &usize| {
+            let name = match depth {
+                0 => "off".to_string(),
+                1 => "1 room".to_string(),
+                n => format!("{} rooms", n),
+            };
+            html! {
+                <option value=depth.to_string() selected=*depth == self.lookahead_depth>
+                    { name }
+                </option>
+            }
+        };
+        let crawl = match &self.crawl {
+            Some(crawl) => crawl,
+            None => {
+                return html! {
+                    <div id="crawl">
+                        <select onchange=|data| Model::concurrency_changed(data)>
+                            { for CRAWL_CONCURRENCIES.iter().map(view_concurrency) }
+                        </select>
+                        <button class="btn btn--inverted" style="margin-left: 5px;"
+                            onclick=|_| Msg::CrawlStart>
+                            { "Crawl the whole maze" }
+                        </button>
+                        { " Look ahead: " }
+                        <select onchange=|data| Model::depth_changed(data)>
+                            { for LOOKAHEAD_DEPTHS.iter().map(view_depth) }
+                        </select>
+                    </div>
+                }
+            }
+        };
+        let progress = format!(
+            "Crawling: {} rooms found, {} open exits, {} requests in flight, {} failed, \
+             {:.1} requests/s",
+            self.state.rooms().count(),
+            crawl.open_frontier(&self.state),
+            crawl.in_flight(),
+            crawl.failed(),
+            crawl.requests_per_second(now_ms())
+        );
+        html! {
+            <div id="crawl">
+                { progress }
+                <button class="btn btn--inverted" style="margin-left: 5px;"
+                    onclick=|_| Msg::CrawlStop>
+                    { "Stop (Esc)" }
+                </button>
+            </div>
+        }
+    }
+
+    fn depth_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Select(select) => select
+                .value()
+                .and_then(|depth| depth.parse().ok())
+                .map(Msg::SetLookaheadDepth)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn concurrency_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Select(select) => select
+                .value()
+                .and_then(|concurrency| concurrency.parse().ok())
+                .map(Msg::SetCrawlConcurrency)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn strategy_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Select(select) => select
+                .value()
+                .map(Msg::SelectStrategy)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn speed_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Select(select) => select
+                .value()
+                .and_then(|delay_ms| delay_ms.parse().ok())
+                .map(Msg::SetAutopilotSpeed)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn view_move_queue(&self) -> Html<Model> {
+        if self.move_queue.is_empty() {
+            return html! { <p id="move-queue"></p> };
+        }
+        let view_move = |direction: &MoveDirection| {
+            html! { <span class="tag">{ direction.short_name() }</span> }
+        };
+        html! {
+            <p id="move-queue">
+                { "Next moves: " }
+                { for self.move_queue.iter().map(view_move) }
+                <button class="btn btn--inverted" style="margin-left: 5px;"
+                    onclick=|_| Msg::ClearMoveQueue>
+                    { "Clear (Esc)" }
+                </button>
+            </p>
+        }
+    }
+
+    /// The best guess of the exit, relative to the current room.
+    fn exit_guess(&self) -> Option<Coordinate> {
+        let best = self.state.exit_candidates().best()?;
+        let current = self.state.current_coordinates()?;
+        Some(Coordinate {
+            x: best.x - current.x,
+            y: best.y - current.y,
+        })
+    }
+
     fn view_map(&self) -> Html<Model> {
         const DISPLAY_NONE: &'static str = "display: none";
         const MAP_BORDER: &'static str = "border: 2px solid black";
-        let (div_style, map_style) = match &self.state.status {
+        let (div_style, map_style) = match self.state.status() {
             Status::Loading => (DISPLAY_NONE, ""),
             Status::InRoom(_) => ("", MAP_BORDER),
             Status::Finished(_) => ("", MAP_BORDER),
         };
+        let view_option = |mode: &MapMode| {
+            html! {
+                <option value=mode.name() selected=*mode == self.map_mode>{ mode.name() }</option>
+            }
+        };
+        let view_export = |format: &MapFormat| {
+            let format = *format;
+            html! {
+                <button class="btn btn--inverted" style="margin-left: 5px;"
+                    onclick=|_| Msg::ExportMap(format)>
+                    { format.name() }
+                </button>
+            }
+        };
         html! {
             <div style=div_style>
                 <h3>{ "Map" }</h3>
+                <p>
+                    { "Rooms by: " }
+                    <select onchange=|data| Model::map_mode_changed(data)>
+                        { for MapMode::ALL.iter().map(view_option) }
+                    </select>
+                    { " Export: " }
+                    { for MapFormat::ALL.iter().map(view_export) }
+                </p>
+                { self.view_travel() }
                 <canvas id="pathbot-map-canvas"
                     style=map_style
+                    onclick=|e| Msg::MapClicked(e.offset_x(), e.offset_y())
                     width="500" height="300"></canvas>
             </div>
         }
     }
-}
 
-// Fetch
-impl Model {
-    fn fetch(&mut self, request: FetchRoomRequest) {
-        if self.fetching {
-            warn!("Not sending, ongoing request.");
-            return;
+    /// Click a room on the map, or choose it here, to go there.
+    fn view_travel(&self) -> Html<Model> {
+        if !self.route.is_empty() {
+            return html! {
+                <p id="travel">
+                    { format!("Travelling, {} moves left.", self.route.len()) }
+                    <button class="btn btn--inverted" style="margin-left: 5px;"
+                        onclick=|_| Msg::CancelTravel>
+                        { "Stop (Esc)" }
+                    </button>
+                </p>
+            };
         }
-        self.fetching = true;
-        self.fetching_move = None;
 
-        // Save the fetched direction
-        self.fetching_move = match &request {
-            FetchRoomRequest::StartRoom => None,
-            FetchRoomRequest::NextRoom(_, dir) => Some(*dir),
+        let mut rooms: Vec<_> = self
+            .state
+            .rooms()
+            .filter(|(id, _, _)| Some(*id) != self.state.current_room_id())
+            .collect();
+        rooms.sort_by_key(|(_, _, coordinate)| (coordinate.y, coordinate.x));
+        let view_option = |(id, room, coordinate): (&RoomId, &Room, Coordinate)| {
+            let kind = room.traits().map(|traits| traits.kind).unwrap_or_default();
+            html! {
+                <option value=id>
+                    { format!("({}, {}) {}", coordinate.x, coordinate.y, kind) }
+                </option>
+            }
         };
+        html! {
+            <p id="travel">
+                { "Travel to: " }
+                <select onchange=|data| Model::travel_changed(data)>
+                    <option value="" selected=true>{ "(click a room on the map)" }</option>
+                    { for rooms.into_iter().map(view_option) }
+                </select>
+            </p>
+        }
+    }
 
-        // Check if the room is cached
-        match &request {
-            FetchRoomRequest::NextRoom(_, move_direction) => {
-                // Compute the next room's coordinates
-                let cur = self
-                    .state
-                    .current_coordinates()
-                    .expect("Logic error: must have a room");
-                let next_coords = cur + move_direction.delta();
-
-                if let Some(room_id) = self.state.coord_to_id.get(&next_coords) {
-                    self.fetching = false;
-                    self.link.send_self(Msg::MoveToRoom(room_id.clone()));
-                    return;
-                };
+    fn travel_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Select(select) => select
+                .value()
+                .filter(|id| !id.is_empty())
+                .map(Msg::TravelTo)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn view_library(&self) -> Html<Model> {
+        let can_save = self.profile.is_some() && !self.state.loading();
+        let save_button = match can_save {
+            true => html! {
+                <button class="btn btn--primary" style="margin-left: 5px;"
+                    onclick=|_| Msg::SaveToLibrary>
+                    { "Save current maze" }
+                </button>
+            },
+            false => html! {
+                <button class="btn btn--inverted" style="margin-left: 5px;">
+                    { "Save current maze" }
+                </button>
+            },
+        };
+        let view_slot = |slot: &save::Slot| {
+            let id = slot.id;
+            let exit = match slot.exit_found {
+                true => "exit found",
+                false => "",
+            };
+            html! {
+                <tr>
+                    <td><img src=&slot.thumbnail alt="" /></td>
+                    <td>
+                        <input type="text" value=&slot.name
+                            onchange=|data| Model::slot_renamed(id, data) />
+                    </td>
+                    <td>{ &slot.profile }</td>
+                    <td>{ format!("{} rooms", slot.rooms) }</td>
+                    <td>{ exit }</td>
+                    <td>{ iso_8601(slot.saved_ms) }</td>
+                    <td>
+                        <button class="btn btn--primary" style="margin-left: 5px;"
+                            onclick=|_| Msg::LoadSlot(id)>
+                            { "Load" }
+                        </button>
+                        <button class="btn btn--inverted" style="margin-left: 5px;"
+                            onclick=|_| Msg::DuplicateSlot(id)>
+                            { "Duplicate" }
+                        </button>
+                        <button class="btn btn--inverted" style="margin-left: 5px;"
+                            onclick=|_| Msg::ExportSlot(id)>
+                            { "Export" }
+                        </button>
+                        <button class="btn btn--inverted" style="margin-left: 5px;"
+                            onclick=|_| Msg::DeleteSlot(id)>
+                            { "Delete" }
+                        </button>
+                    </td>
+                </tr>
             }
-            _ => {}
+        };
+        html! {
+            <details id="library">
+                <summary>{ format!("Saved mazes ({})", self.library.slots().len()) }</summary>
+                <div>
+                    <input type="text" placeholder="Name" value=&self.slot_name
+                        oninput=|e| Msg::SlotNameChanged(e.value) />
+                    { save_button }
+                    { " Import: " }
+                    <input type="file" accept=".json"
+                        onchange=|data| Model::slot_file_changed(data) />
+                </div>
+                <table>
+                    { for self.library.slots().iter().map(view_slot) }
+                </table>
+            </details>
         }
+    }
 
-        // Build the request
-        let (request, last_move): (Request<Text>, _) = match request {
-            FetchRoomRequest::StartRoom => (
-                Request::post("https://api.noopschallenge.com/pathbot/start")
-                    .header("Content-Type", "application/json")
-                    .body(Ok("".to_string()))
-                    .unwrap(), // cannot really fail (except OOM)
-                None,
-            ),
-            FetchRoomRequest::NextRoom(location_path, move_direction) => {
-                let url = format!("https://api.noopschallenge.com{}", location_path);
-                let body = json!({ "direction": move_direction });
-                (
-                    Request::post(url)
-                        .header("Content-Type", "application/json")
-                        .body(Json(&body).into())
-                        .unwrap(), // cannot really fail (except OOM)
-                    Some(move_direction),
-                )
+    fn slot_renamed(id: SlotId, data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Value(name) => Msg::RenameSlot(id, name),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn slot_file_changed(data: ChangeData) -> Msg {
+        match data {
+            ChangeData::Files(files) => files
+                .iter()
+                .next()
+                .map(Msg::ImportSlot)
+                .unwrap_or(Msg::Noop),
+            _ => Msg::Noop,
+        }
+    }
+
+    fn view_journal(&self) -> Html<Model> {
+        let view_entry = |entry: &JournalEntry| {
+            let (status, latency, body) = match &entry.response {
+                Some(response) => (
+                    response.status.to_string(),
+                    format!("{:.0} ms", response.latency_ms),
+                    response.body.clone(),
+                ),
+                None => (
+                    "failed".to_string(),
+                    "".to_string(),
+                    entry.error.clone().unwrap_or_default(),
+                ),
+            };
+            html! {
+                <tr>
+                    <td>{ iso_8601(entry.started) }</td>
+                    <td>{ &entry.request.method }</td>
+                    <td>{ &entry.request.url }</td>
+                    <td><code>{ &entry.request.body }</code></td>
+                    <td>{ status }</td>
+                    <td>{ latency }</td>
+                    <td><code>{ body }</code></td>
+                </tr>
             }
         };
+        html! {
+            <details id="journal">
+                <summary>{ format!("Network ({})", self.journal.len()) }</summary>
+                <div>
+                    <button class="btn btn--inverted" style="margin-left: 5px;"
+                        onclick=|_| Msg::ExportJournal(JournalFormat::Har)>
+                        { "Export HAR" }
+                    </button>
+                    <button class="btn btn--inverted" style="margin-left: 5px;"
+                        onclick=|_| Msg::ExportJournal(JournalFormat::JsonLines)>
+                        { "Export JSON Lines" }
+                    </button>
+                    <button class="btn btn--inverted" style="margin-left: 5px;"
+                        onclick=|_| Msg::ExportJournal(JournalFormat::Recording)>
+                        { "Export recording" }
+                    </button>
+                    <button class="btn btn--inverted" style="margin-left: 5px;"
+                        onclick=|_| Msg::ClearJournal>
+                        { "Clear" }
+                    </button>
+                </div>
+                <table>
+                    <tr>
+                        <th>{ "Time" }</th>
+                        <th>{ "Method" }</th>
+                        <th>{ "URL" }</th>
+                        <th>{ "Request" }</th>
+                        <th>{ "Status" }</th>
+                        <th>{ "Latency" }</th>
+                        <th>{ "Response" }</th>
+                    </tr>
+                    // Newest first
+                    { for self.journal.entries().rev().map(view_entry) }
+                </table>
+            </details>
+        }
+    }
+}
 
-        // Send the request
+// Session
+impl Model {
+    fn load_config(&mut self) {
+        let request = Request::get(config::CONFIG_URL)
+            .body(Nothing)
+            .unwrap(); // cannot really fail (except OOM)
         let callback = self
             .link
-            .send_back(move |response: Response<Json<Result<_, Error>>>| {
-                let (_meta, Json(data)) = response.into_parts();
-                match data {
-                    Ok(PathbotApiMessage::Room(room)) => Msg::ReceivedRoom(room, last_move),
-                    Ok(PathbotApiMessage::Message(message)) => Msg::ReceivedMessage(message),
-                    Ok(PathbotApiMessage::Exit(exit)) => Msg::ReceivedExit(exit, last_move),
-                    Err(e) => Msg::FetchRoomFailed(e),
+            .send_back(|response: Response<Toml<Result<Config, Error>>>| {
+                let (meta, Toml(config)) = response.into_parts();
+                match config {
+                    Ok(config) if meta.status.is_success() => Msg::ConfigLoaded(config),
+                    Ok(_) => {
+                        warn!("No {}, using the default configuration.", config::CONFIG_URL);
+                        Msg::ConfigLoaded(Config::default())
+                    }
+                    Err(e) => {
+                        error!("Invalid {}: {}", config::CONFIG_URL, e);
+                        Msg::ConfigLoaded(Config::default())
+                    }
                 }
             });
-        let task = self.fetch_service.fetch(request, callback);
-        self.fetch_task = Some(task);
+        self.config_task = Some(self.fetch_service.fetch(request, callback));
     }
-}
 
-impl State {
-    fn restart(&mut self) {
-        self.status = Status::Loading;
-        self.rooms.clear();
+    /// Enters a new maze.
+    fn start_session(&mut self, profile: Profile) {
+        self.use_profile(profile);
+        self.state.restart();
+        self.contradictions.clear();
+        self.fetch(FetchRoomRequest::StartRoom);
     }
 
-    fn exited(&self) -> bool {
-        match &self.status {
-            Status::Finished(_) => true,
-            _ => false,
+    /// Enters a saved maze, where it was left.
+    fn resume_session(&mut self, profile: Profile, state: State) {
+        info!("Resuming a maze of {} rooms.", state.rooms().count());
+        self.use_profile(profile);
+        self.state = state;
+        // Already known, not to notify again
+        self.contradictions = self.state.infer().contradictions().iter().cloned().collect();
+        if self.state.current_room_id().is_some() {
+            map::draw(&self.state, self.map_mode);
+            self.prefetch_near();
         }
     }
 
-    fn current_exit_hint(&self) -> Option<MazeExitHint> {
-        match &self.status {
-            Status::InRoom(id) => self
-                .rooms
-                .get(id)
-                .map(|t| &t.0)
-                .map(|r| r.maze_exit_hint.clone()),
-            _ => None,
+    /// Drops everything from the previous backend.
+    fn use_profile(&mut self, profile: Profile) {
+        info!("Using the {} API: {:?}", profile.name, profile.backend);
+        self.transport = Some(transport::for_backend(&profile.backend));
+        self.profile = Some(profile);
+        self.previous_save = None;
+
+        self.fetch_done();
+        self.move_queue.clear();
+        self.stop_travel();
+        self.pause_autopilot();
+        self.stop_crawl();
+        self.stop_prefetch();
+        self.reset_strategy();
+    }
+
+    fn load_previous_save(&mut self) {
+        let json: Result<String, Error> = self.storage.restore(save::STORAGE_KEY);
+        let json = match json {
+            Ok(json) => json,
+            Err(_) => return,
+        };
+        match save::decode(&json) {
+            Ok(save) => self.previous_save = Some(save),
+            Err(e) => {
+                warn!("Invalid save: {}", e);
+                self.link.send_self(Msg::NewNotification(Notification {
+                    message: format!("The previous maze can't be resumed: {}.", e),
+                    level: NotificationLevel::Warning,
+                }));
+            }
         }
     }
 
-    fn current_exits(&self) -> Option<&Vec<MoveDirection>> {
-        match &self.status {
-            Status::InRoom(id) => self.rooms.get(id).map(|t| &t.0.exits),
-            _ => None,
+    /// Resumes a save with its profile, the only one where its location
+    /// paths make sense.
+    fn resume_save(&mut self, save: Save) {
+        match self.config.profile(&save.profile).cloned() {
+            Some(profile) => self.resume_session(profile, save.state),
+            None => {
+                warn!("No {} profile to resume the maze with.", save.profile);
+                self.link.send_self(Msg::NewNotification(Notification {
+                    message: format!(
+                        "This maze can't be resumed: it was explored with the {} API, \
+                         which isn't configured anymore.",
+                        save.profile
+                    ),
+                    level: NotificationLevel::Warning,
+                }));
+            }
         }
     }
 
-    fn current_coordinates(&self) -> Option<Coordinate> {
-        match &self.status {
-            Status::InRoom(id) => self.rooms.get(id).map(|t| t.1.clone()),
-            _ => None,
+    fn load_library(&mut self) {
+        let json: Result<String, Error> = self.storage.restore(save::LIBRARY_KEY);
+        let json = match json {
+            Ok(json) => json,
+            Err(_) => return,
+        };
+        match Library::decode(&json) {
+            Ok(library) => self.library = library,
+            Err(e) => {
+                warn!("Invalid library: {}", e);
+                self.link.send_self(Msg::NewNotification(Notification {
+                    message: format!("The saved mazes can't be listed: {}.", e),
+                    level: NotificationLevel::Danger,
+                }));
+            }
         }
     }
 
-    fn current_room_id(&self) -> Option<&RoomId> {
-        match &self.status {
-            Status::InRoom(id) => Some(id),
-            _ => None,
+    /// Returns false if the storage is full.
+    fn store_library(&self) -> bool {
+        let stored = services::store_locally(save::LIBRARY_KEY, &self.library.encode());
+        if !stored {
+            warn!("Cannot store the library of {} mazes.", self.library.slots().len());
         }
+        stored
     }
 
-    fn can_move_direction(&self, direction: MoveDirection) -> bool {
-        if let Some(current_exits) = self.current_exits() {
-            current_exits.contains(&direction)
-        } else {
-            false
+    /// Adds a save to the library, with `json` as its content.
+    fn add_to_library(&mut self, name: &str, save: &Save, json: &str) {
+        let id = self.library.add(name, save, map::thumbnail(&save.state));
+        if self.store_new_slot(id, json) {
+            info!("Saved {} rooms as {}.", save.rooms(), name);
         }
     }
 
-    fn insert_room(&mut self, room: Room, last_move: Option<MoveDirection>) {
-        // TODO: There are too many clone here
-        let location_path = room.location_path.clone();
+    /// Stores the content of a slot just added to the library, and the
+    /// library. If the storage is full, the slot is removed from the library,
+    /// and the player warned.
+    fn store_new_slot(&mut self, id: SlotId, json: &str) -> bool {
+        let key = save::slot_key(id);
+        if services::store_locally(&key, json) && self.store_library() {
+            return true;
+        }
+        warn!("Cannot store slot {} ({} bytes).", id, json.len());
+        self.library.remove(id);
+        self.storage.remove(&key);
+        self.link.send_self(Msg::NewNotification(Notification {
+            message: "The maze can't be saved, the storage of the browser is full.".to_string(),
+            level: NotificationLevel::Warning,
+        }));
+        false
+    }
 
-        // Save the room, with its position
-        let position = match last_move {
-            Some(prev_move) => {
-                let prev_id = match &self.status {
-                    Status::InRoom(id) => id,
-                    _ => panic!(format!(
-                        "Logic error: cannot insert room when status == {:?}.",
-                        self.status
-                    )),
-                };
-                let prev_position = self
-                    .rooms
-                    .get(prev_id)
-                    .cloned()
-                    .expect("Logic error: room must exist.")
-                    .1;
-                prev_position + prev_move.delta()
-            }
-            None => Coordinate { x: 0, y: 0 },
-        };
-        self.rooms
-            .insert(room.location_path.clone(), (room, position));
-
-        // Add to the caches
-        self.room_coords.insert(location_path.clone(), position);
-        self.coord_to_id.insert(position, location_path);
-    }
-
-    fn reached_exit(&mut self, exit: Exit, last_move: Option<MoveDirection>) -> String {
-        let exit_move = match last_move.expect("Logic error: we must have moved here.") {
-            MoveDirection::N => MoveDirection::S,
-            MoveDirection::S => MoveDirection::N,
-            MoveDirection::E => MoveDirection::W,
-            MoveDirection::W => MoveDirection::E,
-        };
-
-        // Fake a room (for the map)
-        // TODO: Shouldn't have to fake a room
-        let exit_room_id = "exit_room_yay".to_string();
-        let room = Room {
-            status: RoomStatus::Finished,
-            message: "Thank you for playing :)".to_string(),
-            exits: vec![exit_move],
-            description: exit.description.clone(),
-            maze_exit_hint: MazeExitHint {
-                // TODO: Should be None
-                direction: CompassDirection::N,
-                distance: 0,
-            },
-            // TODO: Should be None
-            location_path: exit_room_id.clone(),
-        };
-        self.insert_room(room, last_move);
+    fn restore_slot(&self, id: SlotId) -> Option<String> {
+        let json: Result<String, Error> = self.storage.restore(&save::slot_key(id));
+        match json {
+            Ok(json) => Some(json),
+            Err(e) => {
+                error!("Slot {} not stored: {}", id, e);
+                None
+            }
+        }
+    }
 
-        self.status = Status::Finished(exit);
+    /// Saves the state in the local storage soon, to resume it later.
+    fn save_state(&mut self) {
+        if self.save_task.is_none() {
+            let delay = Duration::from_millis(SAVE_DELAY_MS);
+            let callback = self.link.send_back(|_| Msg::SaveState);
+            self.save_task = Some(self.timeout_service.spawn(delay, callback));
+        }
+    }
 
-        exit_room_id
+    /// Saves the state right away, with the changes waiting for `save_state`:
+    /// a reload right after a move doesn't lose it.
+    fn save_state_now(&mut self) {
+        self.save_task = None;
+        self.store_state();
     }
 
-    fn draw_map(&self) {
-        let canvas: CanvasElement = document()
-            .query_selector("#pathbot-map-canvas")
-            .unwrap()
-            .expect("Didn't find the map canvas.")
-            .try_into() // Element -> CanvasElement
-            .unwrap(); // cannot be other than a canvas
-        let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
+    fn store_state(&mut self) {
+        let profile = match &self.profile {
+            Some(profile) if !self.state.loading() => profile,
+            _ => return,
+        };
+        let json = save::encode(&profile.name, &self.state, now_ms());
+        let stored = services::store_locally(save::STORAGE_KEY, &json);
+        if !stored && !self.save_failed {
+            warn!("Cannot save {} rooms ({} bytes).", self.state.rooms().count(), json.len());
+            self.link.send_self(Msg::NewNotification(Notification {
+                message: "The maze can't be saved anymore, the storage of the browser is full."
+                    .to_string(),
+                level: NotificationLevel::Warning,
+            }));
+        }
+        self.save_failed = !stored;
+    }
+}
 
-        context.clear_rect(0., 0., canvas.width() as f64, canvas.height() as f64);
+// Moves
+impl Model {
+    /// Warns about the rooms that don't agree with their neighbours.
+    fn check_contradictions(&mut self) {
+        for contradiction in self.state.infer().contradictions() {
+            if self.contradictions.insert(contradiction.clone()) {
+                warn!("Contradiction: {}", contradiction);
+                self.link.send_self(Msg::NewNotification(Notification {
+                    message: format!(
+                        "The maze contradicts itself: a room has an exit to the {}, \
+                         but the room there has no exit back.",
+                        contradiction.direction.long_name()
+                    ),
+                    level: NotificationLevel::Warning,
+                }));
+            }
+        }
+    }
 
-        const ROOM_W: f64 = 20.;
-        const ROOM_H: f64 = 20.;
-        const EXIT_L: f64 = 5.;
-        const EXIT_LW: f64 = 2.;
-        const SHIFT_X: f64 = ROOM_W / 2.;
-        const SHIFT_Y: f64 = ROOM_H / 2.;
+    fn fetch_next_room(&mut self, direction: MoveDirection) {
+        let status = self.state.status().clone();
+        match status {
+            Status::Loading => error!("Logic error: no current room."),
+            Status::InRoom(current_room_id) => {
+                self.fetch(FetchRoomRequest::NextRoom(current_room_id, direction));
+            }
+            Status::Finished(_) => error!("Logic error: no more room."),
+        }
+    }
 
-        context.set_line_width(EXIT_LW);
+    /// Goes back or forth in the history, without the API. The moves in
+    /// progress don't make sense anymore.
+    fn navigate_history(&mut self, go: fn(&mut State) -> Option<&RoomId>) -> ShouldRender {
+        if self.loading() {
+            return false;
+        }
+        if go(&mut self.state).is_none() {
+            return false;
+        }
+        self.move_queue.clear();
+        self.stop_travel();
+        self.pause_autopilot();
+        self.save_state_now();
+        map::draw(&self.state, self.map_mode);
+        self.prefetch_near();
+        true
+    }
 
-        let current_room_id = self
-            .current_room_id()
-            .expect("Logic error: must have a current room.");
-        let current_coordinates = self
-            .current_coordinates()
-            .expect("Logic error: must have a current room.");
+    /// Saves a room, and warns about what doesn't fit in the map.
+    fn save_room(&mut self, room: Room, from: Option<(RoomId, MoveDirection)>) {
+        if let Some(traits) = room.traits() {
+            for mismatch in traits.mismatches(&room) {
+                warn!("Room {}: {}", room.location_path, mismatch);
+            }
+        } else {
+            debug!("Unusual description: {}", room.description);
+        }
+
+        let room_id = room.location_path.clone();
+        let warning = match self.state.insert_room_from(room, from) {
+            Some(Conflict::Overlap(other_id)) => {
+                warn!("Rooms {} and {} at the same coordinate.", other_id, room_id);
+                Some(
+                    "This room is where another room already is: the maze isn't a grid, \
+                     the map overlaps.",
+                )
+            }
+            Some(Conflict::Elsewhere {
+                known_at,
+                reached_at,
+            }) => {
+                warn!(
+                    "Room {} known at {:?}, reached again at {:?}.",
+                    room_id, known_at, reached_at
+                );
+                Some(
+                    "This room was already found elsewhere: the maze isn't a grid, the \
+                     map doesn't show this way.",
+                )
+            }
+            None => None,
+        };
+        if let Some(message) = warning {
+            self.link.send_self(Msg::NewNotification(Notification {
+                message: message.to_string(),
+                level: NotificationLevel::Warning,
+            }));
+        }
+        self.check_contradictions();
+    }
+
+    /// Sends the next moves of the crawl, or ends it when there is nothing
+    /// left to explore.
+    fn crawl_more(&mut self) {
+        let (crawl, transport) = match (self.crawl.as_mut(), self.transport.as_mut()) {
+            (Some(crawl), Some(transport)) => (crawl, transport),
+            _ => return,
+        };
+        if crawl.done(&self.state) {
+            let failed = crawl.failed();
+            self.stop_crawl();
+            info!("Crawl done, {} failed moves.", failed);
+            self.link.send_self(Msg::NewNotification(Notification {
+                message: match failed {
+                    0 => "The crawl explored every reachable room.".to_string(),
+                    n => format!(
+                        "The crawl explored every reachable room, except for {} failed moves.",
+                        n
+                    ),
+                },
+                level: NotificationLevel::Info,
+            }));
+            return;
+        }
 
-        let offset_x =
-            canvas.width() as f64 / 2. - current_coordinates.x as f64 * (ROOM_W + EXIT_L);
-        let offset_y =
-            canvas.height() as f64 / 2. - current_coordinates.y as f64 * (ROOM_H + EXIT_L);
+        for (from, direction) in crawl.next_moves(&self.state) {
+            let room_id = from.clone();
+            let callback = self.link.send_back(move |answer: Answer| {
+                Msg::CrawlAnswered(answer, room_id.clone(), direction)
+            });
+            let request = FetchRoomRequest::NextRoom(from.clone(), direction);
+            let task = transport.send(request, callback);
+            crawl.sent((from, direction), task);
+        }
+    }
 
-        // Draw the exits
-        context.begin_path();
-        context.set_fill_style_color("black");
-        for (_, (room, Coordinate { x, y })) in &self.rooms {
-            let origin_x = offset_x + (*x as f64) * (ROOM_W + EXIT_L);
-            let origin_y = offset_y + (*y as f64) * (ROOM_H + EXIT_L);
+    /// Drops the requests of the crawl, which cancels them.
+    fn stop_crawl(&mut self) {
+        self.crawl = None;
+    }
 
-            for exit in &room.exits {
-                use MoveDirection::*;
-                let (from, to) = match exit {
-                    N => ((0., -SHIFT_Y), (0., -SHIFT_Y - EXIT_L)),
-                    W => ((-SHIFT_X, 0.), (-SHIFT_X - EXIT_L, 0.)),
-                    E => ((SHIFT_X, 0.), (SHIFT_X + EXIT_L, 0.)),
-                    S => ((0., SHIFT_Y), (0., SHIFT_Y + EXIT_L)),
-                };
-                context.move_to(origin_x + from.0, origin_y + from.1);
-                context.line_to(origin_x + to.0, origin_y + to.1);
-            }
-        }
-        context.stroke();
-
-        // Draw the rooms
-        for (id, (room, Coordinate { x, y })) in &self.rooms {
-            let room_color = if *x == 0 && *y == 0 {
-                "blue" // initial
-            } else if room.status == RoomStatus::Finished {
-                "green" // exit
-            } else if id == current_room_id {
-                "red" // current
-            } else {
-                "pink" // all other
+    /// Fetches ahead the rooms near the current room, see `lookahead_depth`.
+    fn prefetch_near(&mut self) {
+        let depth = self.lookahead_depth;
+        let (current_room_id, transport) =
+            match (self.state.current_room_id(), self.transport.as_mut()) {
+                (Some(id), Some(transport)) if depth > 0 => (id, transport),
+                _ => return,
             };
 
-            context.set_fill_style_color(room_color);
-            let origin_x = offset_x + (*x as f64) * (ROOM_W + EXIT_L);
-            let origin_y = offset_y + (*y as f64) * (ROOM_H + EXIT_L);
-            context.fill_rect(
-                origin_x - ROOM_W / 2.,
-                origin_y - ROOM_H / 2.,
-                ROOM_W,
-                ROOM_H,
-            );
+        let moves = self
+            .prefetch
+            .next_moves_near(&self.state, current_room_id, depth);
+        for (from, direction) in moves {
+            let room_id = from.clone();
+            let callback = self.link.send_back(move |answer: Answer| {
+                Msg::Prefetched(answer, room_id.clone(), direction)
+            });
+            let request = FetchRoomRequest::NextRoom(from.clone(), direction);
+            let task = transport.send(request, callback);
+            self.prefetch.sent((from, direction), task);
         }
     }
+
+    /// Drops the requests of the prefetch, which cancels them, and forgets
+    /// the failed moves.
+    fn stop_prefetch(&mut self) {
+        self.prefetch = Crawl::new(PREFETCH_CONCURRENCY, now_ms());
+    }
+
+    /// Makes the strategy forget what it did, in another maze.
+    fn reset_strategy(&mut self) {
+        self.strategy =
+            strategy::by_name(self.strategy.name()).expect("Logic error: built-in strategy.");
+    }
+
+    fn pause_autopilot(&mut self) {
+        self.autopilot = false;
+        self.autopilot_task = None;
+    }
+
+    /// Plans a travel from the current room, also through the inferred ways
+    /// back: each move is checked once made.
+    ///
+    /// Returns false, and notifies the player, if there is no known way.
+    fn plan_travel(&mut self, to: RoomId) -> bool {
+        match self.state.likely_path_to(&to) {
+            Some(path) => {
+                info!("Travel to {}: {:?}", to, path);
+                self.route = path.into();
+                self.travel_to = Some(to);
+                true
+            }
+            None => {
+                self.link.send_self(Msg::NewNotification(Notification {
+                    message: "There is no known way to this room.".to_string(),
+                    level: NotificationLevel::Info,
+                }));
+                false
+            }
+        }
+    }
+
+    fn stop_travel(&mut self) {
+        self.route.clear();
+        self.travel_to = None;
+        self.travel_expected = None;
+        self.travel_task = None;
+    }
+
+    fn queue_move(&mut self, direction: MoveDirection) -> ShouldRender {
+        if self.move_queue.len() >= MAX_QUEUED_MOVES {
+            debug!("Move queue full, dropping {:?}.", direction);
+            return false;
+        }
+        self.move_queue.push_back(direction);
+        true
+    }
+
+    /// Runs the next queued move, if it is possible from the current room.
+    /// Otherwise, the following moves don't make sense anymore.
+    fn run_queued_move(&mut self) {
+        let direction = match self.move_queue.pop_front() {
+            Some(direction) => direction,
+            None => return,
+        };
+        if self.state.can_move_direction(direction) {
+            self.fetch_next_room(direction);
+            return;
+        }
+
+        let dropped = self.move_queue.len() + 1;
+        self.move_queue.clear();
+        self.link.send_self(Msg::NewNotification(Notification {
+            message: format!(
+                "There is no exit to the {}, {} queued move{} dropped.",
+                direction.long_name(),
+                dropped,
+                if dropped == 1 { "" } else { "s" }
+            ),
+            level: NotificationLevel::Info,
+        }));
+    }
+}
+
+/// A move of a crawl that failed, or led nowhere, isn't tried again.
+fn crawl_failed(answer: &Answer) -> bool {
+    match &answer.result {
+        Ok(PathbotApiMessage::Room(_)) | Ok(PathbotApiMessage::Exit(_)) => false,
+        _ => true,
+    }
 }
 
-impl MoveDirection {
-    fn delta(&self) -> Coordinate {
-        match &self {
-            MoveDirection::N => Coordinate { x: 0, y: -1 },
-            MoveDirection::S => Coordinate { x: 0, y: 1 },
-            MoveDirection::W => Coordinate { x: -1, y: 0 },
-            MoveDirection::E => Coordinate { x: 1, y: 0 },
+// Fetch
+impl Model {
+    fn fetch(&mut self, request: FetchRoomRequest) {
+        if self.fetching {
+            warn!("Not sending, ongoing request.");
+            return;
+        }
+        self.fetching = true;
+        self.fetching_move = None;
+
+        // Save the fetched direction
+        self.fetching_move = match &request {
+            FetchRoomRequest::StartRoom => None,
+            FetchRoomRequest::NextRoom(_, dir) => Some(*dir),
+        };
+
+        // Check if the room is cached. The exit found by a crawl isn't: the
+        // API must say that we escaped.
+        if let FetchRoomRequest::NextRoom(_, move_direction) = &request {
+            let known_room = self
+                .state
+                .known_destination(*move_direction)
+                .filter(|id| match self.state.room(id) {
+                    Some(room) => room.status == RoomStatus::InProgress,
+                    None => false,
+                });
+            if let Some(room_id) = known_room {
+                self.fetching = false;
+                self.link.send_self(Msg::MoveToRoom(room_id.clone()));
+                return;
+            };
+        }
+
+        self.retries = 0;
+        self.send(request);
+    }
+
+    /// Sends a request through the transport, without any check.
+    fn send(&mut self, request: FetchRoomRequest) {
+        self.pending_request = Some(request.clone());
+
+        let last_move = self.fetching_move;
+        let callback = self
+            .link
+            .send_back(move |answer: Answer| Msg::FetchAnswered(answer, last_move));
+        let transport = self
+            .transport
+            .as_mut()
+            .expect("Logic error: must have a transport.");
+        self.fetch_task = transport.send(request, callback);
+    }
+
+    /// Warns about the fields that the API didn't send before.
+    fn check_extra_keys(&mut self, message: &PathbotApiMessage) {
+        for key in message.extra().keys() {
+            if self.extra_keys.insert(key.clone()) {
+                warn!("The API sent an unknown field: {}", key);
+            }
         }
     }
+
+    /// Forgets the pending request. Dropping its tasks cancels it.
+    fn fetch_done(&mut self) {
+        self.fetching = false;
+        self.fetching_move = None;
+        self.fetch_task = None;
+        self.pending_request = None;
+        self.retries = 0;
+        self.retry_task = None;
+    }
 }
@@ -0,0 +1,464 @@
+//! Map rendering on `#pathbot-map-canvas`
+use stdweb::{
+    traits::*,
+    unstable::TryInto,
+    web::{
+        document, html_element::CanvasElement, CanvasRenderingContext2d, TextAlign,
+        TextBaseline,
+    },
+};
+
+use crate::core::{Coordinate, EdgeKind, Knowledge, RoomId, State};
+use crate::pathbot_api::*;
+
+pub(crate) const ROOM_W: f64 = 20.;
+pub(crate) const ROOM_H: f64 = 20.;
+pub(crate) const EXIT_L: f64 = 5.;
+const EXIT_LW: f64 = 2.;
+const SHIFT_X: f64 = ROOM_W / 2.;
+const SHIFT_Y: f64 = ROOM_H / 2.;
+/// Shift of each room found at an already used coordinate.
+pub(crate) const LAYER_SHIFT: f64 = ROOM_W / 3.;
+/// Opacity of the rooms fetched ahead, but not entered yet.
+pub(crate) const UNVISITED_ALPHA: f64 = 0.4;
+/// Colors of the rooms by status, and of the passages. Also used by
+/// `export`, in hex for Graphviz.
+pub(crate) const START_COLOR: &str = "#0000ff";
+pub(crate) const EXIT_COLOR: &str = "#008000";
+pub(crate) const CURRENT_COLOR: &str = "#ff0000";
+pub(crate) const ROOM_COLOR: &str = "#ffc0cb";
+pub(crate) const CONFIRMED_COLOR: &str = "#000000";
+pub(crate) const INFERRED_COLOR: &str = "#808080";
+/// Last rooms of the history drawn on the map, and shown in the
+/// breadcrumbs.
+pub const HISTORY_SHOWN: usize = 8;
+/// Size of `#pathbot-map-canvas`.
+const MAP_W: u32 = 500;
+const MAP_H: u32 = 300;
+/// Size of the map in the thumbnails of the saved mazes.
+const THUMBNAIL_SCALE: f64 = 0.3;
+
+/// How the rooms are colored and labelled.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum MapMode {
+    Status,
+    Color,
+    Brightness,
+    Shape,
+    Kind,
+}
+
+impl MapMode {
+    pub const ALL: [MapMode; 5] = [
+        MapMode::Status,
+        MapMode::Color,
+        MapMode::Brightness,
+        MapMode::Shape,
+        MapMode::Kind,
+    ];
+
+    pub fn name(self) -> &'static str {
+        use MapMode::*;
+        match self {
+            Status => "status",
+            Color => "color",
+            Brightness => "brightness",
+            Shape => "shape",
+            Kind => "room type",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<MapMode> {
+        MapMode::ALL.iter().cloned().find(|mode| mode.name() == name)
+    }
+
+    /// The trait shown by this mode, if any.
+    fn room_trait(self, room: &Room) -> Option<String> {
+        let traits = room.traits()?;
+        use MapMode::*;
+        match self {
+            Status => None,
+            Color => traits.color,
+            Brightness => traits.brightness,
+            Shape => Some(traits.shape),
+            Kind => Some(traits.kind),
+        }
+    }
+}
+
+/// Where the rooms are drawn on the canvas, centered on the current room.
+struct Layout<'a> {
+    state: &'a State,
+    offset_x: f64,
+    offset_y: f64,
+}
+
+impl<'a> Layout<'a> {
+    fn new(state: &'a State, canvas: &CanvasElement) -> Self {
+        let mut layout = Layout {
+            state,
+            offset_x: 0.,
+            offset_y: 0.,
+        };
+        let current_room_id = state
+            .current_room_id()
+            .expect("Logic error: must have a current room.");
+        let (x, y) = layout
+            .center(current_room_id)
+            .expect("Logic error: must have a current room.");
+        layout.offset_x = canvas.width() as f64 / 2. - x;
+        layout.offset_y = canvas.height() as f64 / 2. - y;
+        layout
+    }
+
+    /// Rooms at the same coordinate are shifted, in their order of discovery.
+    fn center(&self, id: &RoomId) -> Option<(f64, f64)> {
+        let coordinate = self.state.room_coordinates(id)?;
+        let shift = self.state.room_layer(id) as f64 * LAYER_SHIFT;
+        let (x, y) = self.cell_center(coordinate);
+        Some((x + shift, y + shift))
+    }
+
+    fn cell_center(&self, coordinate: Coordinate) -> (f64, f64) {
+        (
+            self.offset_x + coordinate.x as f64 * (ROOM_W + EXIT_L),
+            self.offset_y + coordinate.y as f64 * (ROOM_H + EXIT_L),
+        )
+    }
+}
+
+fn canvas() -> CanvasElement {
+    document()
+        .query_selector("#pathbot-map-canvas")
+        .unwrap()
+        .expect("Didn't find the map canvas.")
+        .try_into() // Element -> CanvasElement
+        .unwrap() // cannot be other than a canvas
+}
+
+/// Returns the room drawn at this point of the canvas, if any.
+pub fn room_at_point(state: &State, x: f64, y: f64) -> Option<RoomId> {
+    state.current_room_id()?;
+    let layout = Layout::new(state, &canvas());
+    state
+        .rooms()
+        .filter(|(id, _, _)| match layout.center(id) {
+            Some((center_x, center_y)) => {
+                (x - center_x).abs() <= ROOM_W / 2. && (y - center_y).abs() <= ROOM_H / 2.
+            }
+            None => false,
+        })
+        // The top one
+        .max_by_key(|(id, _, _)| state.room_layer(id))
+        .map(|(id, _, _)| id.clone())
+}
+
+pub fn draw(state: &State, mode: MapMode) {
+    draw_on(&canvas(), state, mode);
+}
+
+/// A small picture of the map of the state, as a PNG data URL. Empty
+/// before the first room.
+pub fn thumbnail(state: &State) -> String {
+    if state.current_room_id().is_none() {
+        return String::new();
+    }
+    let new_canvas = |width, height| -> CanvasElement {
+        let canvas: CanvasElement = document()
+            .create_element("canvas")
+            .unwrap() // valid tag name
+            .try_into()
+            .unwrap(); // cannot be other than a canvas
+        canvas.set_width(width);
+        canvas.set_height(height);
+        canvas
+    };
+    let map = new_canvas(MAP_W, MAP_H);
+    draw_on(&map, state, MapMode::Status);
+
+    let (width, height) = (
+        f64::from(MAP_W) * THUMBNAIL_SCALE,
+        f64::from(MAP_H) * THUMBNAIL_SCALE,
+    );
+    let thumbnail = new_canvas(width as u32, height as u32);
+    let context: CanvasRenderingContext2d = thumbnail.get_context().unwrap();
+    // Scaled by the browser, stdweb only draws images
+    js! { @(no_return)
+        @{context}.drawImage(@{map}, 0, 0, @{width}, @{height});
+    }
+    thumbnail.to_data_url(Some("image/png"), None).unwrap_or_default()
+}
+
+fn draw_on(canvas: &CanvasElement, state: &State, mode: MapMode) {
+    let context: CanvasRenderingContext2d = canvas.get_context().unwrap();
+
+    context.clear_rect(0., 0., canvas.width() as f64, canvas.height() as f64);
+
+    context.set_line_width(EXIT_LW);
+
+    let current_room_id = state
+        .current_room_id()
+        .expect("Logic error: must have a current room.");
+    let layout = Layout::new(state, canvas);
+    let center = |id: &RoomId| layout.center(id);
+
+    draw_exit_candidates(&context, &layout);
+
+    // Draw the passages, under the rooms. Inferred ones are dashed, and
+    // hidden by a confirmed way back.
+    for kind in &[EdgeKind::Inferred, EdgeKind::Confirmed] {
+        context.begin_path();
+        match kind {
+            EdgeKind::Inferred => {
+                context.set_stroke_style_color(INFERRED_COLOR);
+                context.set_line_dash(vec![2., 2.]);
+            }
+            EdgeKind::Confirmed => {
+                context.set_stroke_style_color(CONFIRMED_COLOR);
+                context.set_line_dash(vec![]);
+            }
+        }
+        for edge in state.graph().edges().filter(|edge| edge.kind == *kind) {
+            if let (Some(from), Some(to)) = (center(&edge.from), center(&edge.to)) {
+                context.move_to(from.0, from.1);
+                context.line_to(to.0, to.1);
+            }
+        }
+        context.stroke();
+    }
+
+    // Draw the exits that don't lead to a known room yet
+    context.begin_path();
+    context.set_fill_style_color("black");
+    for (id, room, _) in state.rooms() {
+        let (origin_x, origin_y) = center(id).expect("Logic error: room must exist.");
+
+        let unexplored = room
+            .exits
+            .iter()
+            .filter(|exit| state.graph().edge(id, **exit).is_none());
+        for exit in unexplored {
+            let (from, to) = passage_line(*exit);
+            context.move_to(origin_x + from.0, origin_y + from.1);
+            context.line_to(origin_x + to.0, origin_y + to.1);
+        }
+    }
+    context.stroke();
+
+    // Draw the rooms, the overlapping ones on top with a border
+    let mut rooms: Vec<_> = state.rooms().collect();
+    rooms.sort_by_key(|(id, _, _)| state.room_layer(id));
+    for (id, room, coordinate) in rooms {
+        let (origin_x, origin_y) = center(id).expect("Logic error: room must exist.");
+        let layer = state.room_layer(id);
+        context.set_global_alpha(match state.visited(id) {
+            true => 1.,
+            false => UNVISITED_ALPHA,
+        });
+
+        if mode != MapMode::Status {
+            draw_trait(&context, mode.room_trait(room), origin_x, origin_y);
+            if id == current_room_id {
+                outline(&context, "red", origin_x, origin_y);
+            } else if layer > 0 {
+                outline(&context, "orange", origin_x, origin_y);
+            }
+            continue;
+        }
+
+        context.set_fill_style_color(status_color(state, id, room, coordinate));
+        context.fill_rect(
+            origin_x - ROOM_W / 2.,
+            origin_y - ROOM_H / 2.,
+            ROOM_W,
+            ROOM_H,
+        );
+        if layer > 0 {
+            outline(&context, "orange", origin_x, origin_y);
+        }
+    }
+    context.set_global_alpha(1.);
+
+    draw_inference(&context, &layout);
+    draw_history(&context, &layout);
+}
+
+/// The color of a room when the rooms are drawn by status.
+pub(crate) fn status_color(
+    state: &State,
+    id: &RoomId,
+    room: &Room,
+    coordinate: Coordinate,
+) -> &'static str {
+    if coordinate == (Coordinate { x: 0, y: 0 }) && state.room_layer(id) == 0 {
+        START_COLOR
+    } else if room.status == RoomStatus::Finished {
+        EXIT_COLOR
+    } else if Some(id) == state.current_room_id() {
+        CURRENT_COLOR
+    } else {
+        ROOM_COLOR
+    }
+}
+
+/// Draws the path through the last rooms of the history.
+fn draw_history(context: &CanvasRenderingContext2d, layout: &Layout) {
+    let steps = layout.state.history().recent(HISTORY_SHOWN);
+    let centers: Vec<_> = steps
+        .iter()
+        .filter_map(|step| layout.center(&step.room_id))
+        .collect();
+    if centers.len() < 2 {
+        return;
+    }
+
+    context.begin_path();
+    context.set_stroke_style_color("rgba(0, 0, 255, 0.5)");
+    context.set_line_dash(vec![]);
+    context.set_line_width(2. * EXIT_LW);
+    context.move_to(centers[0].0, centers[0].1);
+    for (x, y) in &centers[1..] {
+        context.line_to(*x, *y);
+    }
+    context.stroke();
+    context.set_line_width(EXIT_LW);
+    context.set_stroke_style_color("black");
+}
+
+/// Draws the walls: observed ones in black, inferred ones dashed. Also
+/// draws the inferred passages of the unknown rooms, and the exits without
+/// a way back in red.
+fn draw_inference(context: &CanvasRenderingContext2d, layout: &Layout) {
+    let state = layout.state;
+    let inference = state.infer();
+    let center = |coordinate| layout.cell_center(coordinate);
+    let styles: [(Knowledge, &str, Vec<f64>); 3] = [
+        (Knowledge::ObservedWall, "black", vec![]),
+        (Knowledge::InferredWall, "gray", vec![2., 2.]),
+        (Knowledge::InferredPassage, "gray", vec![2., 2.]),
+    ];
+    for (knowledge, color, dash) in styles.iter() {
+        context.begin_path();
+        context.set_stroke_style_color(color);
+        context.set_line_dash(dash.clone());
+        for (coordinate, sides) in inference.cells() {
+            let (x, y) = center(coordinate);
+            for &direction in MoveDirection::ALL.iter() {
+                if sides.get(direction) != *knowledge {
+                    continue;
+                }
+                let (from, to) = match knowledge {
+                    // Into the unknown room, facing the exit of its neighbour
+                    Knowledge::InferredPassage => {
+                        let (border, out) = passage_line(direction);
+                        (border, (2. * border.0 - out.0, 2. * border.1 - out.1))
+                    }
+                    _ => wall_line(direction),
+                };
+                context.move_to(x + from.0, y + from.1);
+                context.line_to(x + to.0, y + to.1);
+            }
+        }
+        context.stroke();
+    }
+
+    context.begin_path();
+    context.set_stroke_style_color("red");
+    context.set_line_dash(vec![]);
+    for contradiction in inference.contradictions() {
+        if let Some(coordinate) = state.room_coordinates(&contradiction.from) {
+            let (x, y) = center(coordinate);
+            let (from, to) = passage_line(contradiction.direction);
+            context.move_to(x + from.0, y + from.1);
+            context.line_to(x + to.0, y + to.1);
+        }
+    }
+    context.stroke();
+    context.set_stroke_style_color("black");
+}
+
+/// Shades the cells where the exit can be, and circles the best guess.
+fn draw_exit_candidates(context: &CanvasRenderingContext2d, layout: &Layout) {
+    let candidates = layout.state.exit_candidates();
+    context.set_fill_style_color("rgba(0, 128, 0, 0.15)");
+    for coordinate in candidates.cells() {
+        let (x, y) = layout.cell_center(coordinate);
+        context.fill_rect(x - ROOM_W / 2., y - ROOM_H / 2., ROOM_W, ROOM_H);
+    }
+
+    if let Some(best) = candidates.best() {
+        let (x, y) = layout.cell_center(best);
+        context.begin_path();
+        context.set_stroke_style_color("green");
+        context.set_line_dash(vec![]);
+        context.arc(x, y, ROOM_W / 3., 0., 2. * std::f64::consts::PI, false);
+        context.stroke();
+        context.set_stroke_style_color("black");
+    }
+}
+
+/// The side of a room, relative to its center.
+fn wall_line(direction: MoveDirection) -> ((f64, f64), (f64, f64)) {
+    use MoveDirection::*;
+    match direction {
+        N => ((-SHIFT_X, -SHIFT_Y), (SHIFT_X, -SHIFT_Y)),
+        S => ((-SHIFT_X, SHIFT_Y), (SHIFT_X, SHIFT_Y)),
+        W => ((-SHIFT_X, -SHIFT_Y), (-SHIFT_X, SHIFT_Y)),
+        E => ((SHIFT_X, -SHIFT_Y), (SHIFT_X, SHIFT_Y)),
+    }
+}
+
+/// The exit of a room, relative to its center.
+fn passage_line(direction: MoveDirection) -> ((f64, f64), (f64, f64)) {
+    use MoveDirection::*;
+    match direction {
+        N => ((0., -SHIFT_Y), (0., -SHIFT_Y - EXIT_L)),
+        W => ((-SHIFT_X, 0.), (-SHIFT_X - EXIT_L, 0.)),
+        E => ((SHIFT_X, 0.), (SHIFT_X + EXIT_L, 0.)),
+        S => ((0., SHIFT_Y), (0., SHIFT_Y + EXIT_L)),
+    }
+}
+
+fn outline(context: &CanvasRenderingContext2d, color: &str, origin_x: f64, origin_y: f64) {
+    context.set_stroke_style_color(color);
+    context.stroke_rect(
+        origin_x - ROOM_W / 2.,
+        origin_y - ROOM_H / 2.,
+        ROOM_W,
+        ROOM_H,
+    );
+    context.set_stroke_style_color("black");
+}
+
+/// Fills the room with a color picked from the trait, and labels it with
+/// the first letters of the trait.
+fn draw_trait(
+    context: &CanvasRenderingContext2d,
+    room_trait: Option<String>,
+    origin_x: f64,
+    origin_y: f64,
+) {
+    let (color, label) = match &room_trait {
+        Some(room_trait) => {
+            let hash = room_trait
+                .bytes()
+                .fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b as u32));
+            let label: String = room_trait.chars().take(2).collect();
+            (format!("hsl({}, 70%, 70%)", hash % 360), label)
+        }
+        None => ("lightgray".to_string(), "?".to_string()),
+    };
+
+    context.set_fill_style_color(&color);
+    context.fill_rect(
+        origin_x - ROOM_W / 2.,
+        origin_y - ROOM_H / 2.,
+        ROOM_W,
+        ROOM_H,
+    );
+    context.set_fill_style_color("black");
+    context.set_font("10px sans-serif");
+    context.set_text_align(TextAlign::Center);
+    context.set_text_baseline(TextBaseline::Middle);
+    context.fill_text(&label, origin_x, origin_y, Some(ROOM_W));
+}
@@ -0,0 +1,355 @@
+//! Seeded maze generation, answering like the Pathbot API.
+//!
+//! The same seed always produces the same maze, room ids and descriptions,
+//! on every platform. See docs/API.md for the shape of the answers.
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::pathbot_api::*;
+
+const START_MESSAGE: &str = "You find yourself in a strange room. You're not sure how you got \
+                             here but you know you need to escape, somehow.";
+const ROOM_MESSAGE: &str = "You are trapped in a maze";
+const EXIT_DESCRIPTION: &str = "Congratulations! You have escaped the maze.";
+
+const ROOMS_PATH: &str = "/pathbot/rooms/";
+
+/// The most rooms in a generated maze: bigger ones take too long to generate,
+/// and too much memory, e.g. in a browser.
+pub const MAX_ROOMS: usize = 1_000_000;
+
+pub struct Maze {
+    width: u32,
+    height: u32,
+    /// Exits of each cell, indexed by `y * width + x`.
+    exits: Vec<Vec<MoveDirection>>,
+    descriptions: Vec<String>,
+    ids: Vec<String>,
+    id_to_cell: HashMap<String, usize>,
+    start: usize,
+    /// The cell having the door to the outside, and the door direction.
+    exit: (usize, MoveDirection),
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum MazeError {
+    UnknownRoom(LocationPath),
+    NoExit(MoveDirection),
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeError::UnknownRoom(path) => write!(f, "There is no room at {}.", path),
+            MazeError::NoExit(direction) => write!(
+                f,
+                "You can't go {}, there is a wall.",
+                direction.long_name()
+            ),
+        }
+    }
+}
+
+impl Maze {
+    /// Generates a perfect maze (exactly one path between two rooms) with a
+    /// recursive backtracker, then opens a door to the outside on its border.
+    ///
+    /// The size must be valid, see `valid_size`.
+    pub fn generate(width: u32, height: u32, seed: u64) -> Maze {
+        let cell_count = room_count(width, height).unwrap_or_else(|| {
+            panic!(
+                "A {}x{} maze must have between 1 and {} rooms.",
+                width, height, MAX_ROOMS
+            )
+        });
+        let mut rng = Rng::new(seed);
+
+        let mut exits = vec![Vec::new(); cell_count];
+        let mut visited = vec![false; cell_count];
+        let mut stack = vec![rng.below(cell_count)];
+        visited[stack[0]] = true;
+        while let Some(&cell) = stack.last() {
+            let candidates: Vec<_> = ALL_DIRECTIONS
+                .iter()
+                .filter_map(|&dir| neighbour(width, height, cell, dir).map(|n| (dir, n)))
+                .filter(|&(_, n)| !visited[n])
+                .collect();
+            if candidates.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let (dir, next) = candidates[rng.below(candidates.len())];
+            exits[cell].push(dir);
+            exits[next].push(dir.opposite());
+            visited[next] = true;
+            stack.push(next);
+        }
+
+        // Open the door to the outside
+        let border: Vec<_> = (0..cell_count)
+            .flat_map(|cell| {
+                ALL_DIRECTIONS
+                    .iter()
+                    .filter(move |&&dir| neighbour(width, height, cell, dir).is_none())
+                    .map(move |&dir| (cell, dir))
+            })
+            .collect();
+        let exit = border[rng.below(border.len())];
+        exits[exit.0].push(exit.1);
+
+        let mut start = rng.below(cell_count);
+        if cell_count > 1 {
+            while start == exit.0 {
+                start = rng.below(cell_count);
+            }
+        }
+
+        for cell_exits in exits.iter_mut() {
+            cell_exits.sort_by_key(|dir| dir.angle_deg() as u32);
+        }
+
+        let ids: Vec<_> = (0..cell_count).map(|_| rng.room_id()).collect();
+        let id_to_cell = ids.iter().cloned().enumerate().map(|(i, id)| (id, i)).collect();
+        let adjectives: Vec<_> = (0..cell_count).map(|_| rng.room_adjectives()).collect();
+
+        let mut maze = Maze {
+            width,
+            height,
+            exits,
+            descriptions: Vec::new(),
+            ids,
+            id_to_cell,
+            start,
+            exit,
+        };
+        maze.descriptions = adjectives
+            .into_iter()
+            .enumerate()
+            .map(|(cell, adjectives)| maze.describe(cell, adjectives))
+            .collect();
+        maze
+    }
+
+    /// Whether a maze of this size can be generated: it has rooms, but not
+    /// more than `MAX_ROOMS`.
+    pub fn valid_size(width: u32, height: u32) -> bool {
+        room_count(width, height).is_some()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Answer to `POST /pathbot/start`.
+    pub fn start(&self) -> Room {
+        let mut room = self.room(self.start);
+        room.message = START_MESSAGE.to_string();
+        room
+    }
+
+    /// Answer to `POST /pathbot/rooms/:id`.
+    pub fn move_from(
+        &self,
+        location_path: &str,
+        direction: MoveDirection,
+    ) -> Result<PathbotApiMessage, MazeError> {
+        let cell = self
+            .location_to_cell(location_path)
+            .ok_or_else(|| MazeError::UnknownRoom(location_path.to_string()))?;
+        if !self.exits[cell].contains(&direction) {
+            return Err(MazeError::NoExit(direction));
+        }
+
+        if (cell, direction) == self.exit {
+            return Ok(PathbotApiMessage::Exit(Exit {
+                status: RoomStatus::Finished,
+                description: EXIT_DESCRIPTION.to_string(),
+                extra: Extra::new(),
+            }));
+        }
+
+        let next = neighbour(self.width, self.height, cell, direction)
+            .expect("Logic error: exits must lead to a cell.");
+        Ok(PathbotApiMessage::Room(self.room(next)))
+    }
+
+    fn location_to_cell(&self, location_path: &str) -> Option<usize> {
+        if !location_path.starts_with(ROOMS_PATH) {
+            return None;
+        }
+        self.id_to_cell
+            .get(&location_path[ROOMS_PATH.len()..])
+            .cloned()
+    }
+
+    fn room(&self, cell: usize) -> Room {
+        Room {
+            status: RoomStatus::InProgress,
+            message: ROOM_MESSAGE.to_string(),
+            exits: self.exits[cell].clone(),
+            description: self.descriptions[cell].clone(),
+            maze_exit_hint: self.exit_hint(cell),
+            location_path: format!("{}{}", ROOMS_PATH, self.ids[cell]),
+            extra: Extra::new(),
+        }
+    }
+
+    /// The distance is the Manhattan distance to the outside of the exit
+    /// door, so it is a lower bound of the number of moves to escape.
+    fn exit_hint(&self, cell: usize) -> MazeExitHint {
+        let (x, y) = self.cell_position(cell);
+        let (exit_x, exit_y) = self.cell_position(self.exit.0);
+        let (door_dx, door_dy) = self.exit.1.unit();
+        let dx = exit_x + door_dx - x;
+        let dy = exit_y + door_dy - y;
+
+        MazeExitHint {
+            // The outside of the door is never a room of the maze
+            direction: CompassDirection::towards(dx, dy).unwrap_or(CompassDirection::N),
+            distance: (dx.abs() + dy.abs()) as u32,
+        }
+    }
+
+    fn describe(&self, cell: usize, adjectives: (&str, &str, &str)) -> String {
+        let (look, shape, kind) = adjectives;
+        let article = match look.chars().next() {
+            Some('a') | Some('e') | Some('i') | Some('o') | Some('u') => "an",
+            _ => "a",
+        };
+        let exits: Vec<_> = self.exits[cell]
+            .iter()
+            .map(|dir| dir.long_name())
+            .collect();
+        let exits = match exits.len() {
+            1 => format!("an exit to the {}", exits[0]),
+            n => format!(
+                "exits to the {} and {}",
+                exits[..n - 1].join(", "),
+                exits[n - 1]
+            ),
+        };
+        let hint = self.exit_hint(cell);
+        format!(
+            "You are in {} {} {} {} with {}. You sense that the maze's exit is to the {}, \
+             at least {} rooms away..",
+            article,
+            look,
+            shape,
+            kind,
+            exits,
+            hint.direction.long_name(),
+            hint.distance
+        )
+    }
+
+    fn cell_position(&self, cell: usize) -> (i64, i64) {
+        (
+            (cell % self.width as usize) as i64,
+            (cell / self.width as usize) as i64,
+        )
+    }
+}
+
+const ALL_DIRECTIONS: [MoveDirection; 4] = [
+    MoveDirection::N,
+    MoveDirection::E,
+    MoveDirection::S,
+    MoveDirection::W,
+];
+
+impl MoveDirection {
+    fn unit(self) -> (i64, i64) {
+        use MoveDirection::*;
+        match self {
+            N => (0, -1),
+            S => (0, 1),
+            E => (1, 0),
+            W => (-1, 0),
+        }
+    }
+}
+
+/// The number of rooms of a maze, if it can be generated.
+fn room_count(width: u32, height: u32) -> Option<usize> {
+    (width as usize)
+        .checked_mul(height as usize)
+        .filter(|&count| count > 0 && count <= MAX_ROOMS)
+}
+
+fn neighbour(width: u32, height: u32, cell: usize, direction: MoveDirection) -> Option<usize> {
+    let (width, height) = (width as i64, height as i64);
+    let (x, y) = (cell as i64 % width, cell as i64 / width);
+    let (dx, dy) = direction.unit();
+    let (x, y) = (x + dx, y + dy);
+    if x < 0 || y < 0 || x >= width || y >= height {
+        None
+    } else {
+        Some((y * width + x) as usize)
+    }
+}
+
+/*
+ * Rng
+ */
+
+const COLORS: &[&str] = &[
+    "chartreuse", "crimson", "teal", "amber", "indigo", "ochre", "violet", "ivory", "emerald",
+    "scarlet", "azure", "beige",
+];
+const SHAPES: &[&str] = &[
+    "long", "rectangular", "square", "round", "narrow", "wide", "octagonal", "cramped",
+];
+const KINDS: &[&str] = &[
+    "dining room", "storage room", "library", "kitchen", "bedroom", "hallway", "cellar",
+    "study", "armory", "chapel",
+];
+
+const ID_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const ID_LEN: usize = 22;
+
+/// Small SplitMix64 generator, so that a seed gives the same maze whatever
+/// the platform.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, words: &[&'a str]) -> &'a str {
+        words[self.below(words.len())]
+    }
+
+    /// Same look as the real ids, e.g. `LU62ZaD_SqudPvH3Qt3kJQ`.
+    fn room_id(&mut self) -> String {
+        (0..ID_LEN)
+            .map(|_| ID_ALPHABET[self.below(ID_ALPHABET.len())] as char)
+            .collect()
+    }
+
+    /// Either a brightness or a color, then a shape and a kind of room.
+    fn room_adjectives(&mut self) -> (&'static str, &'static str, &'static str) {
+        let look = if self.below(2) == 0 {
+            self.pick(COLORS)
+        } else {
+            self.pick(BRIGHTNESSES)
+        };
+        (look, self.pick(SHAPES), self.pick(KINDS))
+    }
+}
@@ -2,13 +2,15 @@
 //!
 //! See docs/API.md.
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::convert::From;
+use serde_json::{Map, Value};
+use std::{convert::From, fmt};
 
 /*
  * Public structs
  */
 
-#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+/// Deserialized by `decode`.
+#[derive(Serialize, PartialEq, Debug, Clone)]
 #[serde(untagged)]
 pub enum PathbotApiMessage {
     Room(Room),
@@ -24,10 +26,15 @@ pub struct Room {
     pub description: String,
     pub maze_exit_hint: MazeExitHint,
     pub location_path: LocationPath,
+    pub extra: Extra,
 }
 
 pub type LocationPath = String;
 
+/// The fields that we don't know about, kept as they were received. The API
+/// may add some.
+pub type Extra = Map<String, Value>;
+
 #[derive(PartialEq, Debug, Copy, Clone)]
 pub enum RoomStatus {
     InProgress,
@@ -40,7 +47,7 @@ pub struct MazeExitHint {
     pub distance: u32,
 }
 
-#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Copy, Clone)]
 pub enum MoveDirection {
     N,
     S,
@@ -49,6 +56,13 @@ pub enum MoveDirection {
 }
 
 impl MoveDirection {
+    pub const ALL: [MoveDirection; 4] = [
+        MoveDirection::N,
+        MoveDirection::S,
+        MoveDirection::E,
+        MoveDirection::W,
+    ];
+
     pub fn short_name(self) -> &'static str {
         use MoveDirection::*;
         match self {
@@ -69,6 +83,23 @@ impl MoveDirection {
         }
     }
 
+    pub fn from_long_name(name: &str) -> Option<MoveDirection> {
+        MoveDirection::ALL
+            .iter()
+            .cloned()
+            .find(|d| d.long_name() == name)
+    }
+
+    pub fn opposite(self) -> MoveDirection {
+        use MoveDirection::*;
+        match self {
+            N => S,
+            S => N,
+            E => W,
+            W => E,
+        }
+    }
+
     /// Returns clockwise angle
     pub fn angle_deg(self) -> f32 {
         use MoveDirection::*;
@@ -122,6 +153,14 @@ impl CompassDirection {
         }
     }
 
+    pub fn from_long_name(name: &str) -> Option<CompassDirection> {
+        use CompassDirection::*;
+        [N, S, E, W, NW, NE, SW, SE]
+            .iter()
+            .cloned()
+            .find(|d| d.long_name() == name)
+    }
+
     /// Returns clockwise angle
     pub fn angle_deg(self) -> f32 {
         use CompassDirection::*;
@@ -136,17 +175,95 @@ impl CompassDirection {
             NW => 315.,
         }
     }
+
+    /// The nearest direction of a move of `dx` to the East and `dy` to the
+    /// South, or `None` for no move.
+    pub fn towards(dx: i64, dy: i64) -> Option<CompassDirection> {
+        if dx == 0 && dy == 0 {
+            return None;
+        }
+        // Clockwise angle from the North, the y axis pointing to the South
+        let angle = (dx as f64).atan2(-dy as f64).to_degrees();
+        let sector = ((angle + 360.) / 45.).round() as usize % 8;
+        use CompassDirection::*;
+        Some([N, NE, E, SE, S, SW, W, NW][sector])
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct Message {
     pub message: String,
+    #[serde(flatten)]
+    pub extra: Extra,
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct Exit {
     pub status: RoomStatus,
     pub description: String,
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl PathbotApiMessage {
+    pub fn extra(&self) -> &Extra {
+        match self {
+            PathbotApiMessage::Room(room) => &room.extra,
+            PathbotApiMessage::Message(message) => &message.extra,
+            PathbotApiMessage::Exit(exit) => &exit.extra,
+        }
+    }
+}
+
+/// What a room description tells, e.g. "You are in a chartreuse rectangular
+/// storage room with exits to the North and South. You sense that the maze's
+/// exit is to the North, at least 5 rooms away..".
+#[derive(PartialEq, Debug, Clone)]
+pub struct RoomTraits {
+    /// The first adjective is either a color or a brightness.
+    pub color: Option<String>,
+    pub brightness: Option<String>,
+    pub shape: String,
+    /// E.g. "storage room".
+    pub kind: String,
+    pub exits: Vec<MoveDirection>,
+    pub maze_exit_hint: MazeExitHint,
+}
+
+/// The words of a description that are a brightness, not a color.
+pub const BRIGHTNESSES: &[&str] = &["bright", "dim", "dark", "gloomy", "shadowy", "sunny"];
+
+/// A description that doesn't agree with the fields of its room.
+#[derive(PartialEq, Debug, Clone)]
+pub enum TraitMismatch {
+    Exits {
+        described: Vec<MoveDirection>,
+        actual: Vec<MoveDirection>,
+    },
+    HintDirection {
+        described: CompassDirection,
+        actual: CompassDirection,
+    },
+    HintDistance { described: u32, actual: u32 },
+}
+
+/// Why a JSON value isn't a `PathbotApiMessage`.
+#[derive(PartialEq, Debug, Clone)]
+pub enum DecodeError {
+    NotAnObject,
+    /// Matches none of the variants.
+    UnknownVariant(String),
+    /// Could be more than one variant.
+    Ambiguous(String),
+    MissingField {
+        variant: &'static str,
+        field: &'static str,
+    },
+    InvalidField {
+        variant: &'static str,
+        field: String,
+        error: String,
+    },
 }
 
 /*
@@ -165,6 +282,8 @@ struct RawRoom {
     mazeExitDirection: CompassDirection,
     mazeExitDistance: u32,
     locationPath: String,
+    #[serde(flatten)]
+    extra: Extra,
 }
 
 impl From<RawRoom> for Room {
@@ -179,6 +298,7 @@ impl From<RawRoom> for Room {
                 distance: raw_room.mazeExitDistance,
             },
             location_path: raw_room.locationPath,
+            extra: raw_room.extra,
         }
     }
 }
@@ -193,6 +313,7 @@ impl From<Room> for RawRoom {
             mazeExitDirection: room.maze_exit_hint.direction,
             mazeExitDistance: room.maze_exit_hint.distance,
             locationPath: room.location_path,
+            extra: room.extra,
         }
     }
 }
@@ -221,6 +342,306 @@ impl<'de> Deserialize<'de> for Room {
     }
 }
 
+/*
+ * RoomTraits
+ */
+
+impl Room {
+    pub fn traits(&self) -> Option<RoomTraits> {
+        RoomTraits::parse(&self.description)
+    }
+}
+
+impl RoomTraits {
+    /// Returns `None` if the description doesn't follow the usual pattern.
+    pub fn parse(description: &str) -> Option<RoomTraits> {
+        let rest = description.strip_prefix("You are in ")?;
+        let rest = rest
+            .strip_prefix("a ")
+            .or_else(|| rest.strip_prefix("an "))?;
+        let (room, rest) = rest.split_once(" with ")?;
+        let (exits, rest) = rest.split_once(". You sense that the maze's exit is to the ")?;
+        let (direction, rest) = rest.split_once(", at least ")?;
+        let distance = rest.split(' ').next()?;
+
+        let mut words = room.split(' ');
+        let look = words.next()?;
+        let shape = words.next()?;
+        let kind = words.collect::<Vec<_>>().join(" ");
+        if kind.is_empty() {
+            return None;
+        }
+        let (color, brightness) = match BRIGHTNESSES.contains(&look) {
+            true => (None, Some(look.to_string())),
+            false => (Some(look.to_string()), None),
+        };
+
+        // "an exit to the North", or "exits to the North, East and South"
+        let exits = exits
+            .strip_prefix("an exit to the ")
+            .or_else(|| exits.strip_prefix("exits to the "))?
+            .split(", ")
+            .flat_map(|names| names.split(" and "))
+            .map(MoveDirection::from_long_name)
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(RoomTraits {
+            color,
+            brightness,
+            shape: shape.to_string(),
+            kind,
+            exits,
+            maze_exit_hint: MazeExitHint {
+                direction: CompassDirection::from_long_name(direction)?,
+                distance: distance.parse().ok()?,
+            },
+        })
+    }
+
+    /// Compares the description with the fields of the room.
+    pub fn mismatches(&self, room: &Room) -> Vec<TraitMismatch> {
+        let mut mismatches = Vec::new();
+        let same_exits = self.exits.len() == room.exits.len()
+            && self.exits.iter().all(|exit| room.exits.contains(exit));
+        if !same_exits {
+            mismatches.push(TraitMismatch::Exits {
+                described: self.exits.clone(),
+                actual: room.exits.clone(),
+            });
+        }
+        let (described, actual) = (self.maze_exit_hint, room.maze_exit_hint);
+        if described.direction != actual.direction {
+            mismatches.push(TraitMismatch::HintDirection {
+                described: described.direction,
+                actual: actual.direction,
+            });
+        }
+        if described.distance != actual.distance {
+            mismatches.push(TraitMismatch::HintDistance {
+                described: described.distance,
+                actual: actual.distance,
+            });
+        }
+        mismatches
+    }
+}
+
+impl fmt::Display for TraitMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = |exits: &[MoveDirection]| {
+            exits
+                .iter()
+                .map(|exit| exit.short_name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        use TraitMismatch::*;
+        match self {
+            Exits { described, actual } => write!(
+                f,
+                "the description has exits {}, but the room has {}",
+                names(described),
+                names(actual)
+            ),
+            HintDirection { described, actual } => write!(
+                f,
+                "the description senses the exit to the {}, but the hint is {}",
+                described.long_name(),
+                actual.long_name()
+            ),
+            HintDistance { described, actual } => write!(
+                f,
+                "the description senses the exit {} rooms away, but the hint is {}",
+                described, actual
+            ),
+        }
+    }
+}
+
+/*
+ * PathbotApiMessage
+ */
+
+const ROOM_FIELDS: [&str; 7] = [
+    "status",
+    "message",
+    "exits",
+    "description",
+    "mazeExitDirection",
+    "mazeExitDistance",
+    "locationPath",
+];
+const MESSAGE_FIELDS: [&str; 1] = ["message"];
+const EXIT_FIELDS: [&str; 2] = ["status", "description"];
+
+/// The fields of a room that a message or an exit doesn't have.
+const ROOM_ONLY_FIELDS: [&str; 4] = [
+    "exits",
+    "mazeExitDirection",
+    "mazeExitDistance",
+    "locationPath",
+];
+
+/// Decodes a Pathbot message, picking the variant from its fields:
+/// - a room has a `locationPath`, and isn't `finished`,
+/// - an exit has a `finished` status, and none of the room fields,
+/// - a message has only a `message`.
+///
+/// Unlike an untagged enum, this tells which field is wrong, and doesn't
+/// take a broken room for a message. The unknown fields go to `extra`.
+pub fn decode(value: &Value) -> Result<PathbotApiMessage, DecodeError> {
+    let object = value.as_object().ok_or(DecodeError::NotAnObject)?;
+
+    if object.contains_key("locationPath") {
+        let finished = object
+            .get("status")
+            .and_then(|status| RoomStatus::deserialize(status).ok())
+            == Some(RoomStatus::Finished);
+        if finished {
+            return Err(DecodeError::Ambiguous(
+                "a room with a `finished` status".to_string(),
+            ));
+        }
+        return decode_room(object).map(PathbotApiMessage::Room);
+    }
+
+    match object.get("status") {
+        Some(_) => match field::<RoomStatus>(object, "status", "status")? {
+            RoomStatus::Finished => {
+                if let Some(room_fields) = room_only_fields(object) {
+                    return Err(DecodeError::Ambiguous(format!(
+                        "an exit with the room fields {}",
+                        room_fields
+                    )));
+                }
+                decode_exit(object).map(PathbotApiMessage::Exit)
+            }
+            RoomStatus::InProgress => Err(DecodeError::MissingField {
+                variant: "room",
+                field: "locationPath",
+            }),
+        },
+        None if object.contains_key("message") => {
+            if let Some(room_fields) = room_only_fields(object) {
+                return Err(DecodeError::Ambiguous(format!(
+                    "a message with the room fields {}, but no `status`",
+                    room_fields
+                )));
+            }
+            Ok(PathbotApiMessage::Message(Message {
+                message: field(object, "message", "message")?,
+                extra: extra(object, &MESSAGE_FIELDS),
+            }))
+        }
+        None => Err(DecodeError::UnknownVariant(
+            "no `locationPath`, `status` or `message`".to_string(),
+        )),
+    }
+}
+
+/// Lists the fields of a room that the object has, if any.
+fn room_only_fields(object: &Map<String, Value>) -> Option<String> {
+    let fields: Vec<_> = ROOM_ONLY_FIELDS
+        .iter()
+        .filter(|f| object.contains_key(**f))
+        .map(|f| format!("`{}`", f))
+        .collect();
+    if fields.is_empty() {
+        None
+    } else {
+        Some(fields.join(", "))
+    }
+}
+
+fn decode_room(object: &Map<String, Value>) -> Result<Room, DecodeError> {
+    const VARIANT: &str = "room";
+    let exits = field::<Vec<Value>>(object, VARIANT, "exits")?
+        .iter()
+        .enumerate()
+        .map(|(idx, exit)| {
+            MoveDirection::deserialize(exit).map_err(|e| DecodeError::InvalidField {
+                variant: VARIANT,
+                field: format!("exits[{}]", idx),
+                error: e.to_string(),
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Room {
+        status: field(object, VARIANT, "status")?,
+        message: field(object, VARIANT, "message")?,
+        exits,
+        description: field(object, VARIANT, "description")?,
+        maze_exit_hint: MazeExitHint {
+            direction: field(object, VARIANT, "mazeExitDirection")?,
+            distance: field(object, VARIANT, "mazeExitDistance")?,
+        },
+        location_path: field(object, VARIANT, "locationPath")?,
+        extra: extra(object, &ROOM_FIELDS),
+    })
+}
+
+fn decode_exit(object: &Map<String, Value>) -> Result<Exit, DecodeError> {
+    const VARIANT: &str = "exit";
+    Ok(Exit {
+        status: field(object, VARIANT, "status")?,
+        description: field(object, VARIANT, "description")?,
+        extra: extra(object, &EXIT_FIELDS),
+    })
+}
+
+fn field<'de, T: Deserialize<'de>>(
+    object: &'de Map<String, Value>,
+    variant: &'static str,
+    name: &'static str,
+) -> Result<T, DecodeError> {
+    let value = object
+        .get(name)
+        .ok_or(DecodeError::MissingField {
+            variant,
+            field: name,
+        })?;
+    T::deserialize(value).map_err(|e| DecodeError::InvalidField {
+        variant,
+        field: name.to_string(),
+        error: e.to_string(),
+    })
+}
+
+fn extra(object: &Map<String, Value>, known_fields: &[&str]) -> Extra {
+    object
+        .iter()
+        .filter(|(key, _)| !known_fields.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+impl<'de> Deserialize<'de> for PathbotApiMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        decode(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use DecodeError::*;
+        match self {
+            NotAnObject => write!(f, "not a JSON object"),
+            UnknownVariant(e) => write!(f, "not a room, a message or an exit: {}", e),
+            Ambiguous(e) => write!(f, "ambiguous answer: {}", e),
+            MissingField { variant, field } => write!(f, "{}: missing field `{}`", variant, field),
+            InvalidField {
+                variant,
+                field,
+                error,
+            } => write!(f, "{}: invalid field `{}`: {}", variant, field, error),
+        }
+    }
+}
+
 /*
  * RoomStatus
  */
@@ -0,0 +1,101 @@
+//! Recorded sessions, to play them again without any server
+//!
+//! A recording holds the answer to the start request, and the answers to
+//! the moves, keyed by location path and direction:
+//!
+//! ```json
+//! {
+//!   "start": { "status": "in-progress", "locationPath": "/pathbot/rooms/a", ... },
+//!   "moves": {
+//!     "/pathbot/rooms/a": { "N": { "status": "in-progress", ... } }
+//!   }
+//! }
+//! ```
+//!
+//! The moves are sorted, so that the same session always gives the same file.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::journal::Journal;
+use crate::pathbot_api::*;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct Recording {
+    pub start: PathbotApiMessage,
+    #[serde(default)]
+    pub moves: BTreeMap<LocationPath, BTreeMap<MoveDirection, PathbotApiMessage>>,
+}
+
+impl Recording {
+    pub fn new(start: PathbotApiMessage) -> Self {
+        Recording {
+            start,
+            moves: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert_move(
+        &mut self,
+        location_path: LocationPath,
+        direction: MoveDirection,
+        answer: PathbotApiMessage,
+    ) {
+        self.moves
+            .entry(location_path)
+            .or_default()
+            .insert(direction, answer);
+    }
+
+    pub fn next_room(
+        &self,
+        location_path: &str,
+        direction: MoveDirection,
+    ) -> Option<&PathbotApiMessage> {
+        self.moves.get(location_path)?.get(&direction)
+    }
+
+    /// Collects the successful answers of a journal, from the last start
+    /// request on: the moves before it were made in another maze.
+    ///
+    /// Returns `None` if the journal has no successful start request.
+    pub fn from_journal(journal: &Journal) -> Option<Recording> {
+        let mut start = None;
+        let mut moves = Vec::new();
+        for entry in journal.entries() {
+            // The location path is the path of the URL, whatever the server
+            let path = match entry.request.url.find("/pathbot/") {
+                Some(idx) => &entry.request.url[idx..],
+                None => continue,
+            };
+            let answer = match &entry.response {
+                Some(response) if (200..300).contains(&response.status) => {
+                    serde_json::from_str::<PathbotApiMessage>(&response.body).ok()
+                }
+                _ => None,
+            };
+            if path == "/pathbot/start" {
+                // A failed start is retried, or leaves no maze to record
+                start = answer;
+                moves.clear();
+                continue;
+            }
+            let answer = match answer {
+                Some(answer) => answer,
+                None => continue,
+            };
+            #[derive(Deserialize)]
+            struct Move {
+                direction: MoveDirection,
+            }
+            if let Ok(Move { direction }) = serde_json::from_str(&entry.request.body) {
+                moves.push((path.to_string(), direction, answer));
+            }
+        }
+
+        let mut recording = Recording::new(start?);
+        for (location_path, direction, answer) in moves {
+            recording.insert_move(location_path, direction, answer);
+        }
+        Some(recording)
+    }
+}
@@ -0,0 +1,55 @@
+//! Retry policy for transient API failures
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::error::PathbotError;
+
+/// Exponential backoff with jitter, configured in the `[retry]` section of
+/// `config.toml`.
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// 0 disables the retries.
+    pub max_retries: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Randomized part of each delay, between 0 and 1.
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 5,
+            initial_delay_ms: 500,
+            max_delay_ms: 10_000,
+            jitter: 0.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait before the given retry (starting at 1), or
+    /// `None` to give up. `random` must be between 0 and 1.
+    ///
+    /// A `Retry-After` sent by the server wins over the backoff, up to
+    /// `max_delay_ms`. Browsers only let us read it if the server also sends
+    /// `Access-Control-Expose-Headers: Retry-After`, otherwise the backoff is
+    /// used.
+    pub fn delay(&self, retry: u32, error: &PathbotError, random: f64) -> Option<Duration> {
+        if retry == 0 || retry > self.max_retries || !error.is_transient() {
+            return None;
+        }
+        if let Some(retry_after) = error.retry_after() {
+            return Some(retry_after.min(Duration::from_millis(self.max_delay_ms)));
+        }
+
+        let backoff = self
+            .initial_delay_ms
+            .saturating_mul(1 << (retry - 1).min(16))
+            .min(self.max_delay_ms) as f64;
+        let jitter = self.jitter.max(0.).min(1.);
+        let delay = backoff * (1. - jitter) + backoff * jitter * random;
+        Some(Duration::from_millis(delay as u64))
+    }
+}
@@ -0,0 +1,248 @@
+//! Saves of the explored maze, kept in the local storage of the browser
+//!
+//! A save is a versioned envelope around the serialized `State`:
+//! `{"version": 1, "save": {"profile": …, "savedMs": …, "state": …}}`. A
+//! save of an older version is migrated, if possible.
+//!
+//! Besides the save resumed on startup, the `Library` keeps named copies of
+//! mazes, each one under its own key, `slot_key`. A slot file, for import and
+//! export, is the same envelope.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{convert::TryFrom, fmt};
+
+use crate::core::State;
+use crate::pathbot_api::RoomStatus;
+
+/// Key of the save in the local storage.
+pub const STORAGE_KEY: &str = "pathbot.save";
+/// Key of the `Library` in the local storage.
+pub const LIBRARY_KEY: &str = "pathbot.library";
+/// Bumped when the saved `State` changes, with its migration in `migrate`.
+pub const VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Save {
+    /// The API profile the maze was explored with: the location paths only
+    /// make sense there.
+    pub profile: String,
+    /// Milliseconds since the Unix epoch.
+    pub saved_ms: f64,
+    pub state: State,
+}
+
+impl Save {
+    pub fn rooms(&self) -> usize {
+        self.state.rooms().count()
+    }
+
+    pub fn exit_found(&self) -> bool {
+        self.state
+            .rooms()
+            .any(|(_, room, _)| room.status == RoomStatus::Finished)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum SaveError {
+    /// Not even an envelope.
+    NotASave(String),
+    /// Saved by a newer version of Pathbot.
+    Newer(u32),
+    /// Too old to be migrated.
+    Unsupported(u32),
+    /// The envelope is fine, its content isn't.
+    Invalid(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::NotASave(error) => write!(f, "this isn't a Pathbot save ({})", error),
+            SaveError::Newer(version) => write!(
+                f,
+                "it was saved by a newer version of Pathbot (save version {}, this one \
+                 reads up to {})",
+                version, VERSION
+            ),
+            SaveError::Unsupported(version) => write!(
+                f,
+                "it was saved by an old version of Pathbot that can't be read anymore \
+                 (save version {})",
+                version
+            ),
+            SaveError::Invalid(error) => write!(f, "the save is damaged ({})", error),
+        }
+    }
+}
+
+/// The envelope of a `Save` being written, borrowing its content.
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u32,
+    save: SaveRef<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SaveRef<'a> {
+    profile: &'a str,
+    saved_ms: f64,
+    state: &'a State,
+}
+
+/// Serializes a state, without cloning it.
+pub fn encode(profile: &str, state: &State, saved_ms: f64) -> String {
+    let envelope = Envelope {
+        version: VERSION,
+        save: SaveRef {
+            profile,
+            saved_ms,
+            state,
+        },
+    };
+    serde_json::to_string(&envelope).unwrap_or_default()
+}
+
+pub fn decode(json: &str) -> Result<Save, SaveError> {
+    let mut envelope: Value =
+        serde_json::from_str(json).map_err(|e| SaveError::NotASave(e.to_string()))?;
+    let version = envelope
+        .get("version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| SaveError::NotASave("no version".to_string()))?;
+    let save = envelope
+        .get_mut("save")
+        .map(Value::take)
+        .ok_or_else(|| SaveError::NotASave("no save".to_string()))?;
+
+    let version = u32::try_from(version)
+        .map_err(|_| SaveError::NotASave(format!("invalid version {}", version)))?;
+    let save = migrate(version, save)?;
+    let mut save: Save =
+        serde_json::from_value(save).map_err(|e| SaveError::Invalid(e.to_string()))?;
+    save.state.rebuild();
+    Ok(save)
+}
+
+/// Brings a save of an older version up to `VERSION`. There is no older
+/// version yet.
+fn migrate(version: u32, save: Value) -> Result<Value, SaveError> {
+    match version {
+        VERSION => Ok(save),
+        version if version > VERSION => Err(SaveError::Newer(version)),
+        version => Err(SaveError::Unsupported(version)),
+    }
+}
+
+pub type SlotId = u32;
+
+/// Key of the content of a slot in the local storage.
+pub fn slot_key(id: SlotId) -> String {
+    format!("pathbot.slot.{}", id)
+}
+
+/// The name of a slot imported from a file: the file name, without its
+/// `.json` extension.
+pub fn slot_name_from_file(filename: &str) -> &str {
+    match filename.strip_suffix(".json") {
+        Some("") | None => filename,
+        Some(name) => name,
+    }
+}
+
+/// What the library shows of a saved maze, without decoding it.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Slot {
+    pub id: SlotId,
+    pub name: String,
+    pub profile: String,
+    pub rooms: usize,
+    pub exit_found: bool,
+    pub saved_ms: f64,
+    /// A PNG data URL of the map, empty if there is none.
+    pub thumbnail: String,
+}
+
+/// The named saves, in the order they were added.
+#[derive(Serialize, Deserialize, Default, PartialEq, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Library {
+    slots: Vec<Slot>,
+    next_id: SlotId,
+}
+
+impl Library {
+    pub fn decode(json: &str) -> Result<Library, SaveError> {
+        serde_json::from_str(json).map_err(|e| SaveError::Invalid(e.to_string()))
+    }
+
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    pub fn slot(&self, id: SlotId) -> Option<&Slot> {
+        self.slots.iter().find(|slot| slot.id == id)
+    }
+
+    /// Adds the summary of a save. Its content is to be stored under the
+    /// `slot_key` of the id returned.
+    pub fn add(&mut self, name: &str, save: &Save, thumbnail: String) -> SlotId {
+        let id = self.new_id();
+        self.slots.push(Slot {
+            id,
+            name: name.to_string(),
+            profile: save.profile.clone(),
+            rooms: save.rooms(),
+            exit_found: save.exit_found(),
+            saved_ms: save.saved_ms,
+            thumbnail,
+        });
+        id
+    }
+
+    /// Returns whether the slot exists. A blank name is ignored.
+    pub fn rename(&mut self, id: SlotId, name: &str) -> bool {
+        let name = name.trim();
+        match self.slots.iter_mut().find(|slot| slot.id == id) {
+            Some(slot) if !name.is_empty() => {
+                slot.name = name.to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds a copy of the slot, just after it. Its content is to be copied
+    /// under the `slot_key` of the id returned.
+    pub fn duplicate(&mut self, id: SlotId) -> Option<SlotId> {
+        let index = self.slots.iter().position(|slot| slot.id == id)?;
+        let copy_id = self.new_id();
+        let original = &self.slots[index];
+        let copy = Slot {
+            id: copy_id,
+            name: format!("{} (copy)", original.name),
+            ..original.clone()
+        };
+        self.slots.insert(index + 1, copy);
+        Some(copy_id)
+    }
+
+    pub fn remove(&mut self, id: SlotId) -> Option<Slot> {
+        let index = self.slots.iter().position(|slot| slot.id == id)?;
+        Some(self.slots.remove(index))
+    }
+
+    /// Ids are never reused, even after a removal.
+    fn new_id(&mut self) -> SlotId {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
@@ -0,0 +1,14 @@
+/// Makes the browser save `content` as a file.
+pub fn download(filename: &str, mime_type: &str, content: &str) {
+    js! { @(no_return)
+        var blob = new Blob([@{content}], { type: @{mime_type} });
+        var url = URL.createObjectURL(blob);
+        var a = document.createElement("a");
+        a.href = url;
+        a.download = @{filename};
+        document.body.appendChild(a);
+        a.click();
+        document.body.removeChild(a);
+        URL.revokeObjectURL(url);
+    }
+}
@@ -1,6 +1,8 @@
 // From: https://github.com/s3k/yew-keydown-example/blob/master/src/keydown_service.rs
 //
 use log::*;
+use stdweb::traits::IEvent;
+use stdweb::unstable::TryInto;
 use stdweb::web::event::KeyDownEvent;
 use stdweb::Value;
 use yew::callback::Callback;
@@ -68,3 +70,14 @@ impl Drop for KeydownTask {
         }
     }
 }
+
+/// Whether the key was pressed in a form field, where it edits the field.
+pub fn in_form_field(event: &KeyDownEvent) -> bool {
+    let target = event.target();
+    let in_form_field = js! {
+        var target = @{target};
+        return target != null
+            && ["INPUT", "TEXTAREA", "SELECT"].indexOf(target.tagName) >= 0;
+    };
+    in_form_field.try_into().unwrap_or(false)
+}
@@ -1,3 +1,7 @@
+mod download;
 mod keydown;
+mod storage;
 
-pub use keydown::{KeydownService, KeydownTask};
+pub use download::download;
+pub use keydown::{in_form_field, KeydownService, KeydownTask};
+pub use storage::store_locally;
@@ -0,0 +1,18 @@
+use stdweb::unstable::TryInto;
+
+/// Stores a value in the local storage of the browser.
+///
+/// Returns false if it can't, e.g. when the storage is full. Yew's
+/// `StorageService` panics then.
+pub fn store_locally(key: &str, value: &str) -> bool {
+    let stored = js! {
+        try {
+            localStorage.setItem(@{key}, @{value});
+            return true;
+        } catch (e) {
+            console.warn("Cannot store " + @{key} + ": " + e);
+            return false;
+        }
+    };
+    stored.try_into().unwrap_or(false)
+}
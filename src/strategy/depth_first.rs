@@ -0,0 +1,53 @@
+use super::*;
+use crate::core::RoomId;
+
+/// Takes the first unexplored exit, and backtracks at dead ends. With
+/// nothing to backtrack, e.g. after the player left its path, it goes to the
+/// nearest unexplored exit.
+#[derive(Default, Debug)]
+pub struct DepthFirst {
+    /// The moves to the current room, from which room, to backtrack.
+    path: Vec<(RoomId, MoveDirection)>,
+}
+
+impl DepthFirst {
+    pub const NAME: &'static str = "depth-first";
+
+    /// Keeps the path up to the current room, if the player went back on
+    /// it, or forgets it if the last move didn't lead here.
+    fn follow_player(&mut self, state: &State, current: &RoomId) {
+        if let Some(idx) = self.path.iter().position(|(from, _)| from == current) {
+            self.path.truncate(idx);
+            return;
+        }
+        let reached = self
+            .path
+            .last()
+            .and_then(|(from, direction)| state.graph().edge(from, *direction))
+            .map(|edge| &edge.to);
+        if reached != Some(current) {
+            self.path.clear();
+        }
+    }
+}
+
+impl Strategy for DepthFirst {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn next_move(&mut self, state: &State) -> Option<MoveDirection> {
+        let current = state.current_room_id()?;
+        self.follow_player(state, current);
+        match state.unexplored_exits(current).first() {
+            Some(&direction) => {
+                self.path.push((current.clone(), direction));
+                Some(direction)
+            }
+            None => match self.path.pop() {
+                Some((_, direction)) => Some(direction.opposite()),
+                None => Frontier.next_move(state),
+            },
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+/// Goes to the nearest unexplored exit, through the known rooms: the maze
+/// is explored breadth-first around the current room.
+#[derive(Default, Debug)]
+pub struct Frontier;
+
+impl Frontier {
+    pub const NAME: &'static str = "breadth-first frontier";
+}
+
+impl Strategy for Frontier {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn next_move(&mut self, state: &State) -> Option<MoveDirection> {
+        let (room_id, _, first_move) = state
+            .nearest_rooms()
+            .find(|(id, _, _)| !state.unexplored_exits(id).is_empty())?;
+        first_move.or_else(|| state.unexplored_exits(room_id).first().cloned())
+    }
+}
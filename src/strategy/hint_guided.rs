@@ -0,0 +1,160 @@
+use super::*;
+use crate::core::{EdgeKind, RoomId};
+
+/// Best-first search of the exit, guided by the hints of the rooms.
+///
+/// The distance of a hint is a lower bound of the moves to the exit, and
+/// its compass direction tells which exits look promising. How much the
+/// direction is trusted is learnt from the moves made in the current maze.
+#[derive(Default, Debug)]
+pub struct HintGuided {
+    explanation: Vec<String>,
+}
+
+impl HintGuided {
+    pub const NAME: &'static str = "hint-guided best-first";
+}
+
+/// An unexplored exit, and its estimated cost to the exit.
+#[derive(Debug)]
+struct Candidate<'a> {
+    room: &'a RoomId,
+    exit: MoveDirection,
+    first_move: MoveDirection,
+    /// Moves to go through the exit.
+    moves: usize,
+    /// At least this many more moves to the exit, after going through it.
+    lower_bound: u32,
+    /// Whether the exit goes the way of the hint.
+    along_hint: bool,
+    cost: f64,
+}
+
+/// Extra cost of an exit going away from the hint, when the hint is fully
+/// reliable.
+const AWAY_FROM_HINT_COST: f64 = 2.;
+
+impl Strategy for HintGuided {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn next_move(&mut self, state: &State) -> Option<MoveDirection> {
+        let (reliability, samples) = hint_reliability(state);
+        // One search from the current room gives the way to every room
+        let mut candidates: Vec<_> = state
+            .nearest_rooms()
+            .filter_map(|(id, distance, first_move)| {
+                let hint = state.room(id)?.maze_exit_hint;
+                let candidates = state.unexplored_exits(id).into_iter().map(move |exit| {
+                    let along_hint = hint_moves(hint.direction).contains(&exit);
+                    let moves = distance + 1;
+                    let lower_bound = hint.distance.saturating_sub(1);
+                    let mut cost = moves as f64 + lower_bound as f64;
+                    if !along_hint {
+                        cost += reliability * AWAY_FROM_HINT_COST;
+                    }
+                    Candidate {
+                        room: id,
+                        exit,
+                        first_move: first_move.unwrap_or(exit),
+                        moves,
+                        lower_bound,
+                        along_hint,
+                        cost,
+                    }
+                });
+                Some(candidates)
+            })
+            .flatten()
+            .collect();
+        candidates.sort_by(|a, b| {
+            (a.cost, a.lower_bound, a.moves)
+                .partial_cmp(&(b.cost, b.lower_bound, b.moves))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        self.explanation = explain(state, &candidates, reliability, samples);
+        candidates.first().map(|candidate| candidate.first_move)
+    }
+
+    fn explain(&self) -> Vec<String> {
+        self.explanation.clone()
+    }
+}
+
+/// The moves that go the way of a compass direction.
+fn hint_moves(direction: CompassDirection) -> Vec<MoveDirection> {
+    use CompassDirection::*;
+    use MoveDirection as M;
+    match direction {
+        N => vec![M::N],
+        S => vec![M::S],
+        E => vec![M::E],
+        W => vec![M::W],
+        NE => vec![M::N, M::E],
+        NW => vec![M::N, M::W],
+        SE => vec![M::S, M::E],
+        SW => vec![M::S, M::W],
+    }
+}
+
+/// Learns from the moves made: a reliable hint gets closer to the exit when
+/// moving its way, and farther when moving away.
+///
+/// Returns the ratio of the moves that agreed with the hint (starting at
+/// 50%), and the number of moves.
+fn hint_reliability(state: &State) -> (f64, usize) {
+    let (mut agreed, mut samples) = (1., 2.);
+    let confirmed = state
+        .graph()
+        .edges()
+        .filter(|edge| edge.kind == EdgeKind::Confirmed);
+    for edge in confirmed {
+        let (from, to) = match (state.room(&edge.from), state.room(&edge.to)) {
+            (Some(from), Some(to)) if to.status == RoomStatus::InProgress => (from, to),
+            _ => continue,
+        };
+        let along_hint = hint_moves(from.maze_exit_hint.direction).contains(&edge.direction);
+        let closer = to.maze_exit_hint.distance < from.maze_exit_hint.distance;
+        if along_hint == closer {
+            agreed += 1.;
+        }
+        samples += 1.;
+    }
+    (agreed / samples, samples as usize - 2)
+}
+
+fn explain(
+    state: &State,
+    candidates: &[Candidate],
+    reliability: f64,
+    samples: usize,
+) -> Vec<String> {
+    let mut explanation = vec![format!(
+        "The compass was right {:.0}% of the time ({} moves).",
+        reliability * 100.,
+        samples
+    )];
+    for (idx, candidate) in candidates.iter().take(5).enumerate() {
+        let coordinate = state
+            .room_coordinates(candidate.room)
+            .map(|c| format!("({}, {})", c.x, c.y))
+            .unwrap_or_default();
+        explanation.push(format!(
+            "{}{} from {}: {} move{}, then at least {} more, {} the hint: cost {:.1}.",
+            if idx == 0 { "Chosen: " } else { "" },
+            candidate.exit.long_name(),
+            coordinate,
+            candidate.moves,
+            if candidate.moves == 1 { "" } else { "s" },
+            candidate.lower_bound,
+            if candidate.along_hint { "along" } else { "away from" },
+            candidate.cost
+        ));
+    }
+    if candidates.is_empty() {
+        explanation.push("No unexplored exit left.".to_string());
+    }
+    explanation
+}
@@ -0,0 +1,55 @@
+//! Ways to explore a maze without a player
+//!
+//! A strategy looks at the known rooms and picks the next move, one move at
+//! a time. It may remember what it did, e.g. to backtrack.
+use crate::core::State;
+use crate::pathbot_api::*;
+
+mod depth_first;
+mod frontier;
+mod hint_guided;
+mod tremaux;
+mod wall_follower;
+
+pub use depth_first::DepthFirst;
+pub use frontier::Frontier;
+pub use hint_guided::HintGuided;
+pub use tremaux::Tremaux;
+pub use wall_follower::{Hand, WallFollower};
+
+pub trait Strategy {
+    fn name(&self) -> &'static str;
+
+    /// Returns the next move from the current room, or `None` if there is
+    /// nowhere left to go.
+    fn next_move(&mut self, state: &State) -> Option<MoveDirection>;
+
+    /// Explains the last move, for the player.
+    fn explain(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// The names of the built-in strategies, see `by_name`.
+pub const NAMES: [&str; 6] = [
+    DepthFirst::NAME,
+    Frontier::NAME,
+    HintGuided::NAME,
+    WallFollower::LEFT_NAME,
+    WallFollower::RIGHT_NAME,
+    Tremaux::NAME,
+];
+
+/// Builds a built-in strategy, with no memory.
+pub fn by_name(name: &str) -> Option<Box<dyn Strategy>> {
+    let strategy: Box<dyn Strategy> = match name {
+        DepthFirst::NAME => Box::new(DepthFirst::default()),
+        Frontier::NAME => Box::new(Frontier::default()),
+        HintGuided::NAME => Box::new(HintGuided::default()),
+        WallFollower::LEFT_NAME => Box::new(WallFollower::new(Hand::Left)),
+        WallFollower::RIGHT_NAME => Box::new(WallFollower::new(Hand::Right)),
+        Tremaux::NAME => Box::new(Tremaux::default()),
+        _ => return None,
+    };
+    Some(strategy)
+}
@@ -0,0 +1,68 @@
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+use crate::core::RoomId;
+
+/// Trémaux's algorithm: marks the passages when going through them, never
+/// goes through a passage marked twice, and turns back when reaching a known
+/// room by a new passage.
+#[derive(Default, Debug)]
+pub struct Tremaux {
+    /// Number of times each passage was taken, at both of its ends.
+    marks: HashMap<(RoomId, MoveDirection), u8>,
+    visited: HashSet<RoomId>,
+    /// The last move, from which room.
+    last_move: Option<(RoomId, MoveDirection)>,
+}
+
+impl Tremaux {
+    pub const NAME: &'static str = "Trémaux";
+
+    fn marks(&self, room: &RoomId, direction: MoveDirection) -> u8 {
+        self.marks
+            .get(&(room.clone(), direction))
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    fn mark(&mut self, room: RoomId, direction: MoveDirection) {
+        *self.marks.entry((room, direction)).or_insert(0) += 1;
+    }
+}
+
+impl Strategy for Tremaux {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn next_move(&mut self, state: &State) -> Option<MoveDirection> {
+        let current = state.current_room_id()?.clone();
+        let exits = state.current_exits()?.clone();
+
+        // Mark the passage we came through, if the move was made, and the
+        // player didn't go elsewhere since
+        let entrance = match (self.last_move.take(), state.history().recent(2)) {
+            (Some((from, direction)), [previous, _]) if previous.room_id == from => {
+                self.mark(from, direction);
+                self.mark(current.clone(), direction.opposite());
+                Some(direction.opposite())
+            }
+            _ => None,
+        };
+        let known_room = !self.visited.insert(current.clone());
+
+        let direction = match entrance {
+            // A loop: go back, the passage gets its second mark
+            Some(entrance) if known_room && self.marks(&current, entrance) == 1 => Some(entrance),
+            _ => exits
+                .iter()
+                .cloned()
+                .filter(|exit| Some(*exit) != entrance)
+                .filter(|exit| self.marks(&current, *exit) < 2)
+                .min_by_key(|exit| self.marks(&current, *exit))
+                .or_else(|| entrance.filter(|e| self.marks(&current, *e) < 2)),
+        }?;
+        self.last_move = Some((current, direction));
+        Some(direction)
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Keeps a hand on a wall: turns to that side whenever possible. Escapes
+/// any maze without loops around the exit.
+#[derive(Debug)]
+pub struct WallFollower {
+    hand: Hand,
+    /// The direction of the last move.
+    heading: MoveDirection,
+}
+
+impl WallFollower {
+    pub const LEFT_NAME: &'static str = "left wall follower";
+    pub const RIGHT_NAME: &'static str = "right wall follower";
+
+    pub fn new(hand: Hand) -> Self {
+        WallFollower {
+            hand,
+            heading: MoveDirection::N,
+        }
+    }
+}
+
+impl Strategy for WallFollower {
+    fn name(&self) -> &'static str {
+        match self.hand {
+            Hand::Left => Self::LEFT_NAME,
+            Hand::Right => Self::RIGHT_NAME,
+        }
+    }
+
+    fn next_move(&mut self, state: &State) -> Option<MoveDirection> {
+        let exits = state.current_exits()?;
+        let (side, other_side) = match self.hand {
+            Hand::Left => (left_of(self.heading), left_of(self.heading).opposite()),
+            Hand::Right => (left_of(self.heading).opposite(), left_of(self.heading)),
+        };
+        let preferences = [side, self.heading, other_side, self.heading.opposite()];
+        let direction = preferences.iter().cloned().find(|d| exits.contains(d))?;
+        self.heading = direction;
+        Some(direction)
+    }
+}
+
+fn left_of(direction: MoveDirection) -> MoveDirection {
+    use MoveDirection::*;
+    match direction {
+        N => W,
+        W => S,
+        S => E,
+        E => N,
+    }
+}
@@ -0,0 +1,64 @@
+use super::*;
+
+/// Native client, waiting for the answers.
+pub struct BlockingTransport {
+    base_url: String,
+}
+
+impl BlockingTransport {
+    pub fn new(base_url: String) -> Self {
+        BlockingTransport { base_url }
+    }
+
+    /// Sends the request and waits for the answer.
+    pub fn call(&self, request: FetchRoomRequest) -> Answer {
+        let recorded = http_request(&self.base_url, &request);
+        let mut http_request = ureq::request(&recorded.method, &recorded.url);
+        for (name, value) in &recorded.headers {
+            http_request.set(name, value);
+        }
+        let body = recorded.body.clone();
+        let mut entry = JournalEntry::new(recorded);
+
+        let response = http_request.send_string(&body);
+        if let Some(e) = response.synthetic_error() {
+            entry.failed(e.to_string());
+            return Answer {
+                result: Err(PathbotError::Network(e.to_string())),
+                entry,
+            };
+        }
+
+        let status = response.status();
+        let headers: Vec<_> = response
+            .headers_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = response.header(&name)?.to_string();
+                Some((name, value))
+            })
+            .collect();
+        let result = match response.into_string() {
+            Ok(body) => {
+                entry.received(status, headers.clone(), body.clone());
+                decode_answer(&request, status, &headers, body)
+            }
+            Err(e) => {
+                entry.failed(e.to_string());
+                Err(PathbotError::Network(e.to_string()))
+            }
+        };
+        Answer { result, entry }
+    }
+}
+
+impl PathbotTransport for BlockingTransport {
+    fn send(
+        &mut self,
+        request: FetchRoomRequest,
+        callback: Callback<Answer>,
+    ) -> Option<Box<dyn Task>> {
+        callback.emit(self.call(request));
+        None
+    }
+}
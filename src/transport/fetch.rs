@@ -0,0 +1,66 @@
+use yew::{
+    format::Text,
+    services::fetch::{FetchService, Request, Response},
+};
+
+use super::*;
+
+/// Talks to a Pathbot API server with yew's `FetchService`.
+pub struct FetchTransport {
+    base_url: String,
+    fetch_service: FetchService,
+}
+
+impl FetchTransport {
+    pub fn new(base_url: String) -> Self {
+        FetchTransport {
+            base_url,
+            fetch_service: FetchService::new(),
+        }
+    }
+}
+
+impl PathbotTransport for FetchTransport {
+    fn send(
+        &mut self,
+        request: FetchRoomRequest,
+        callback: Callback<Answer>,
+    ) -> Option<Box<dyn Task>> {
+        let recorded = http_request(&self.base_url, &request);
+        let mut builder = Request::builder();
+        builder.method(recorded.method.as_str()).uri(recorded.url.as_str());
+        for (name, value) in &recorded.headers {
+            builder.header(name.as_str(), value.as_str());
+        }
+        let http_request: Request<Text> = builder
+            .body(Ok(recorded.body.clone()))
+            .unwrap(); // cannot really fail (except OOM)
+
+        let entry = JournalEntry::new(recorded);
+        let on_response = move |response: Response<Text>| {
+            let mut entry = entry.clone();
+            let (meta, body) = response.into_parts();
+            let result = match body {
+                Ok(body) => {
+                    let headers: Vec<_> = meta
+                        .headers
+                        .iter()
+                        .map(|(name, value)| {
+                            let value = value.to_str().unwrap_or_default();
+                            (name.to_string(), value.to_string())
+                        })
+                        .collect();
+                    entry.received(meta.status.as_u16(), headers.clone(), body.clone());
+                    decode_answer(&request, meta.status.as_u16(), &headers, body)
+                }
+                Err(e) => {
+                    entry.failed(e.to_string());
+                    Err(PathbotError::Network(e.to_string()))
+                }
+            };
+            callback.emit(Answer { result, entry });
+        };
+        let task = self.fetch_service.fetch(http_request, on_response.into());
+        Some(Box::new(task))
+    }
+}
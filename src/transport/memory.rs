@@ -0,0 +1,54 @@
+use crate::maze::MazeError;
+
+use super::*;
+
+const MEMORY_URL: &str = "memory:";
+
+/// Answers from a maze generated in-process, without any network.
+pub struct MemoryTransport {
+    maze: Maze,
+}
+
+impl MemoryTransport {
+    pub fn new(maze: Maze) -> Self {
+        MemoryTransport { maze }
+    }
+}
+
+impl PathbotTransport for MemoryTransport {
+    fn send(
+        &mut self,
+        request: FetchRoomRequest,
+        callback: Callback<Answer>,
+    ) -> Option<Box<dyn Task>> {
+        let mut entry = JournalEntry::new(http_request(MEMORY_URL, &request));
+
+        // Same answers as pathbot-server
+        let (status, answer) = match &request {
+            FetchRoomRequest::StartRoom => (200, PathbotApiMessage::Room(self.maze.start())),
+            FetchRoomRequest::NextRoom(location_path, move_direction) => {
+                match self.maze.move_from(location_path, *move_direction) {
+                    Ok(answer) => (200, answer),
+                    Err(e) => {
+                        let status = match e {
+                            MazeError::UnknownRoom(_) => 404,
+                            MazeError::NoExit(_) => 400,
+                        };
+                        let message = Message {
+                            message: e.to_string(),
+                            extra: Extra::new(),
+                        };
+                        (status, PathbotApiMessage::Message(message))
+                    }
+                }
+            }
+        };
+        let headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        let body = serde_json::to_string(&answer).unwrap_or_default();
+        entry.received(status, headers.clone(), body.clone());
+
+        let result = decode_answer(&request, status, &headers, body);
+        callback.emit(Answer { result, entry });
+        None
+    }
+}
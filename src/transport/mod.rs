@@ -0,0 +1,129 @@
+//! Ways to reach a Pathbot API
+//!
+//! The `Model` only talks to a `PathbotTransport`, so that the real API can
+//! be swapped for an in-memory maze, a recorded session or a native client.
+//!
+//! Every answer comes with its journal entry, recording what was sent and
+//! received.
+use serde_json::json;
+use std::time::Duration;
+use yew::{services::Task, Callback};
+
+use crate::config::Backend;
+use crate::error::PathbotError;
+use crate::journal::{JournalEntry, RecordedRequest};
+use crate::maze::Maze;
+use crate::pathbot_api::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod blocking;
+mod fetch;
+mod memory;
+mod replay;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use blocking::BlockingTransport;
+pub use fetch::FetchTransport;
+pub use memory::MemoryTransport;
+pub use replay::ReplayTransport;
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum FetchRoomRequest {
+    StartRoom,
+    NextRoom(LocationPath, MoveDirection),
+}
+
+pub type PathbotResult = Result<PathbotApiMessage, PathbotError>;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Answer {
+    pub result: PathbotResult,
+    pub entry: JournalEntry,
+}
+
+pub trait PathbotTransport {
+    /// Sends the request, and calls `callback` once with the answer.
+    ///
+    /// Dropping the returned task, if any, cancels the request.
+    fn send(
+        &mut self,
+        request: FetchRoomRequest,
+        callback: Callback<Answer>,
+    ) -> Option<Box<dyn Task>>;
+}
+
+/// Builds the transport of an API profile.
+pub fn for_backend(backend: &Backend) -> Box<dyn PathbotTransport> {
+    match backend {
+        Backend::Http { base_url } => Box::new(FetchTransport::new(base_url.clone())),
+        Backend::Memory {
+            width,
+            height,
+            seed,
+        } => Box::new(MemoryTransport::new(Maze::generate(*width, *height, *seed))),
+        Backend::Replay { recording } => Box::new(ReplayTransport::new(recording.clone())),
+    }
+}
+
+/// The HTTP request of a Pathbot request, see docs/API.md.
+fn http_request(base_url: &str, request: &FetchRoomRequest) -> RecordedRequest {
+    let (path, body) = match request {
+        FetchRoomRequest::StartRoom => ("/pathbot/start", "".to_string()),
+        FetchRoomRequest::NextRoom(location_path, move_direction) => (
+            location_path.as_str(),
+            json!({ "direction": move_direction }).to_string(),
+        ),
+    };
+    RecordedRequest {
+        method: "POST".to_string(),
+        url: format!("{}{}", base_url.trim_end_matches('/'), path),
+        headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+        body,
+    }
+}
+
+/// Turns a raw HTTP answer into a Pathbot message.
+fn decode_answer(
+    request: &FetchRoomRequest,
+    status: u16,
+    headers: &[(String, String)],
+    body: String,
+) -> PathbotResult {
+    if !(200..300).contains(&status) {
+        let retry_after = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("retry-after"))
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .map(Duration::from_secs);
+        return Err(PathbotError::Http {
+            status,
+            body,
+            retry_after,
+        });
+    }
+    let answer = serde_json::from_str(&body).map_err(|e| PathbotError::Deserialize {
+        error: e.to_string(),
+        json: body.clone(),
+    })?;
+    check_protocol(request, answer)
+}
+
+/// Rejects the answers that cannot happen in a Pathbot maze.
+fn check_protocol(request: &FetchRoomRequest, answer: PathbotApiMessage) -> PathbotResult {
+    let violation = match (request, &answer) {
+        (FetchRoomRequest::StartRoom, PathbotApiMessage::Exit(_)) => {
+            Some("the maze is finished before starting")
+        }
+        (_, PathbotApiMessage::Room(room)) if room.location_path.is_empty() => {
+            Some("the room has no location")
+        }
+        (_, PathbotApiMessage::Room(room)) if room.exits.is_empty() => {
+            Some("the room has no exit")
+        }
+        _ => None,
+    };
+    match violation {
+        Some(violation) => Err(PathbotError::Protocol(violation.to_string())),
+        None => Ok(answer),
+    }
+}
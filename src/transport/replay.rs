@@ -0,0 +1,127 @@
+use log::*;
+use std::{cell::RefCell, rc::Rc};
+use yew::{
+    format::Text,
+    services::fetch::{FetchService, Request, Response},
+};
+
+use crate::recording::Recording;
+
+use super::*;
+
+const REPLAY_URL: &str = "replay:";
+
+/// Answers from a recorded session, see `recording`.
+///
+/// The recording is downloaded on the first request.
+pub struct ReplayTransport {
+    recording_url: String,
+    recording: Rc<RefCell<Option<Recording>>>,
+    fetch_service: FetchService,
+}
+
+impl ReplayTransport {
+    pub fn new(recording_url: String) -> Self {
+        ReplayTransport {
+            recording_url,
+            recording: Rc::new(RefCell::new(None)),
+            fetch_service: FetchService::new(),
+        }
+    }
+}
+
+impl PathbotTransport for ReplayTransport {
+    fn send(
+        &mut self,
+        request: FetchRoomRequest,
+        callback: Callback<Answer>,
+    ) -> Option<Box<dyn Task>> {
+        if let Some(recording) = &*self.recording.borrow() {
+            callback.emit(replay(recording, request));
+            return None;
+        }
+
+        let http_request = Request::get(self.recording_url.as_str())
+            .body(Ok("".to_string()))
+            .unwrap(); // cannot really fail (except OOM)
+        let download = JournalEntry::new(RecordedRequest {
+            method: "GET".to_string(),
+            url: self.recording_url.clone(),
+            headers: vec![],
+            body: "".to_string(),
+        });
+        let recording = self.recording.clone();
+        let on_response = move |response: Response<Text>| {
+            // Only journaled if the recording cannot be loaded
+            let mut entry = download.clone();
+            let (meta, body) = response.into_parts();
+            let loaded = match body {
+                Ok(body) => {
+                    entry.received(meta.status.as_u16(), vec![], body.clone());
+                    decode_recording(meta.status.as_u16(), body)
+                }
+                Err(e) => {
+                    entry.failed(e.to_string());
+                    Err(PathbotError::Network(e.to_string()))
+                }
+            };
+            match loaded {
+                Ok(loaded) => {
+                    info!("Recording loaded.");
+                    recording.replace(Some(loaded));
+                    if let Some(recording) = &*recording.borrow() {
+                        callback.emit(replay(recording, request.clone()));
+                    }
+                }
+                Err(error) => callback.emit(Answer {
+                    result: Err(error),
+                    entry,
+                }),
+            }
+        };
+        let task = self.fetch_service.fetch(http_request, on_response.into());
+        Some(Box::new(task))
+    }
+}
+
+fn decode_recording(status: u16, body: String) -> Result<Recording, PathbotError> {
+    if !(200..300).contains(&status) {
+        return Err(PathbotError::Http {
+            status,
+            body,
+            retry_after: None,
+        });
+    }
+    serde_json::from_str(&body).map_err(|e| PathbotError::Deserialize {
+        error: e.to_string(),
+        json: body,
+    })
+}
+
+fn replay(recording: &Recording, request: FetchRoomRequest) -> Answer {
+    let mut entry = JournalEntry::new(http_request(REPLAY_URL, &request));
+    let answer = match &request {
+        FetchRoomRequest::StartRoom => Some(&recording.start),
+        FetchRoomRequest::NextRoom(location_path, direction) => {
+            recording.next_room(location_path, *direction)
+        }
+    };
+    let result = match (answer, request) {
+        (Some(answer), request) => {
+            let body = serde_json::to_string(answer).unwrap_or_default();
+            let headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+            entry.received(200, headers.clone(), body.clone());
+            decode_answer(&request, 200, &headers, body)
+        }
+        (None, FetchRoomRequest::NextRoom(location_path, direction)) => {
+            let error = PathbotError::NotInRecording {
+                location_path,
+                direction,
+            };
+            entry.failed(error.to_string());
+            Err(error)
+        }
+        (None, FetchRoomRequest::StartRoom) => unreachable!("a recording always has a start"),
+    };
+    Answer { result, entry }
+}
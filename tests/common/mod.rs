@@ -0,0 +1,75 @@
+//! Helpers shared by the tests
+//!
+//! Each test only uses some of them.
+#![allow(dead_code)]
+
+use pathbot::core::State;
+use pathbot::maze::Maze;
+use pathbot::pathbot_api::*;
+use pathbot::strategy;
+
+/// A room in progress, with the given exits.
+pub fn room(location_path: &str, exits: Vec<MoveDirection>) -> Room {
+    Room {
+        status: RoomStatus::InProgress,
+        message: "You are trapped in a maze".to_string(),
+        exits,
+        description: "".to_string(),
+        maze_exit_hint: MazeExitHint {
+            direction: CompassDirection::N,
+            distance: 3,
+        },
+        location_path: location_path.to_string(),
+        extra: Extra::new(),
+    }
+}
+
+/// Enters the start room of the maze.
+pub fn enter(maze: &Maze) -> State {
+    let mut state = State::default();
+    let start = maze.start();
+    let start_id = start.location_path.clone();
+    state.insert_room(start, None);
+    state.move_to(start_id);
+    state
+}
+
+/// Moves like `Model`: to the known room in that direction, if any, or to
+/// the room the maze answers with.
+///
+/// Returns whether the move escaped the maze.
+pub fn make_move(maze: &Maze, state: &mut State, direction: MoveDirection) -> bool {
+    if let Some(room_id) = state.known_destination(direction) {
+        state.move_to(room_id.clone());
+        return false;
+    }
+    let current = state.current_room_id().unwrap().clone();
+    match maze.move_from(&current, direction).unwrap() {
+        PathbotApiMessage::Room(room) => {
+            let room_id = room.location_path.clone();
+            state.insert_room(room, Some(direction));
+            state.move_to(room_id);
+            false
+        }
+        PathbotApiMessage::Exit(exit) => {
+            let room_id = state.reached_exit(exit, Some(direction));
+            state.move_to(room_id);
+            true
+        }
+        PathbotApiMessage::Message(message) => panic!("{}", message.message),
+    }
+}
+
+/// Explores with the depth-first strategy, for `moves` moves or until the
+/// exit.
+pub fn explore(maze: &Maze, moves: usize) -> State {
+    let mut strategy = strategy::by_name(strategy::DepthFirst::NAME).unwrap();
+    let mut state = enter(maze);
+    for _ in 0..moves {
+        let direction = strategy.next_move(&state).unwrap();
+        if make_move(maze, &mut state, direction) {
+            break;
+        }
+    }
+    state
+}
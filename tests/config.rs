@@ -0,0 +1,61 @@
+//! Checks of the configuration
+use pathbot::config::{Backend, Config, ConfigError, Profile};
+
+#[test]
+fn default_config_is_valid() {
+    assert_eq!(Config::default().check(), Ok(()));
+}
+
+#[test]
+fn empty_maze() {
+    let mut config = Config::default();
+    config.profiles.push(Profile {
+        name: "flat".to_string(),
+        backend: Backend::Memory {
+            width: 10,
+            height: 0,
+            seed: 42,
+        },
+    });
+    assert_eq!(
+        config.check(),
+        Err(ConfigError::EmptyMaze {
+            profile: "flat".to_string()
+        })
+    );
+}
+
+#[test]
+fn huge_maze() {
+    let mut config = Config::default();
+    config.profiles.push(Profile {
+        name: "huge".to_string(),
+        backend: Backend::Memory {
+            width: u32::MAX,
+            height: u32::MAX,
+            seed: 42,
+        },
+    });
+    assert_eq!(
+        config.check(),
+        Err(ConfigError::HugeMaze {
+            profile: "huge".to_string()
+        })
+    );
+}
+
+#[test]
+fn unknown_profile_in_the_query() {
+    let mut config = Config::default();
+    let default_profile = config.default_profile.clone();
+    assert_eq!(
+        config.apply_query("?profile=foo"),
+        Err(ConfigError::UnknownProfile {
+            profile: "foo".to_string()
+        })
+    );
+    assert_eq!(config.default_profile, default_profile);
+
+    assert_eq!(config.apply_query("?profile=local%20mock"), Ok(()));
+    assert_eq!(config.default_profile, "local mock");
+}
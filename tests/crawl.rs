@@ -0,0 +1,84 @@
+//! Crawls of generated mazes, with answers arriving out of order
+use pathbot::core::State;
+use pathbot::crawl::Crawl;
+use pathbot::maze::Maze;
+use pathbot::pathbot_api::*;
+
+mod common;
+use common::enter;
+
+/// Crawls like `Model`, answering the most recent request first, and
+/// returns the state once the crawl is done.
+fn crawl(maze: &Maze, concurrency: usize) -> State {
+    let mut state = enter(maze);
+    let mut crawl: Crawl<()> = Crawl::new(concurrency, 0.);
+    let mut pending = vec![];
+    while !crawl.done(&state) {
+        for crawl_move in crawl.next_moves(&state) {
+            crawl.sent(crawl_move.clone(), None);
+            pending.push(crawl_move);
+        }
+        assert!(crawl.in_flight() <= concurrency);
+
+        let (from, direction) = pending.pop().expect("nothing in flight");
+        let answer = maze.move_from(&from, direction).unwrap();
+        let failed = match answer {
+            PathbotApiMessage::Message(_) => true,
+            _ => false,
+        };
+        assert!(crawl.answered(&(from.clone(), direction), failed));
+        match answer {
+            PathbotApiMessage::Room(room) => {
+                state.insert_room_from(room, Some((from, direction)));
+            }
+            PathbotApiMessage::Exit(exit) => {
+                state.insert_exit(&exit, from, direction);
+            }
+            PathbotApiMessage::Message(_) => {}
+        }
+    }
+    state
+}
+
+#[test]
+fn explores_every_room() {
+    for &concurrency in &[1, 4, 16] {
+        let state = crawl(&Maze::generate(6, 5, 2), concurrency);
+        // And the exit
+        assert_eq!(state.rooms().count(), 6 * 5 + 1);
+        assert_eq!(state.frontier().count(), 0);
+        assert!(!state.exited());
+    }
+}
+
+#[test]
+fn unexpected_answer() {
+    let maze = Maze::generate(3, 3, 0);
+    let mut crawl: Crawl<()> = Crawl::new(2, 0.);
+    let start = maze.start();
+    assert!(!crawl.answered(&(start.location_path, MoveDirection::N), false));
+}
+
+#[test]
+fn lookahead() {
+    let maze = Maze::generate(6, 5, 2);
+    let mut state = enter(&maze);
+    let start_id = state.current_room_id().unwrap().clone();
+    let prefetch: Crawl<()> = Crawl::new(8, 0.);
+
+    let moves = prefetch.next_moves_near(&state, &start_id, 1);
+    assert_eq!(moves.len(), state.current_exits().unwrap().len());
+    for (from, direction) in moves {
+        if let PathbotApiMessage::Room(room) = maze.move_from(&from, direction).unwrap() {
+            state.insert_room_from(room, Some((from, direction)));
+        }
+    }
+    assert!(state.visited(&start_id));
+    assert!(state.rooms().all(|(id, _, _)| *id == start_id || !state.visited(id)));
+
+    // The start room has nothing left, the next ones go farther
+    let moves = prefetch.next_moves_near(&state, &start_id, 2);
+    assert!(!moves.is_empty());
+    assert!(moves.iter().all(|(from, _)| *from != start_id));
+    assert!(prefetch.next_moves_near(&state, &start_id, 1).is_empty());
+}
@@ -0,0 +1,156 @@
+//! Decoding of the Pathbot answers in tests/fixtures
+use pathbot::pathbot_api::*;
+use serde_json::Value;
+
+fn decode_fixture(name: &str) -> Result<PathbotApiMessage, DecodeError> {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let json = std::fs::read_to_string(&path).expect("missing fixture");
+    let value: Value = serde_json::from_str(&json).expect("invalid JSON fixture");
+    decode(&value)
+}
+
+#[test]
+fn room() {
+    match decode_fixture("room.json") {
+        Ok(PathbotApiMessage::Room(room)) => {
+            assert_eq!(room.status, RoomStatus::InProgress);
+            assert_eq!(room.exits, vec![MoveDirection::N, MoveDirection::S]);
+            assert_eq!(room.maze_exit_hint.direction, CompassDirection::N);
+            assert_eq!(room.maze_exit_hint.distance, 5);
+            assert_eq!(room.location_path, "/pathbot/rooms/OkNMk8D_XfLtYgnicZWzcA");
+        }
+        other => panic!("not a room: {:?}", other),
+    }
+}
+
+#[test]
+fn exit() {
+    let expected = Exit {
+        status: RoomStatus::Finished,
+        description: "Congratulations! You have escaped the maze.".to_string(),
+        extra: Extra::new(),
+    };
+    assert_eq!(decode_fixture("exit.json"), Ok(PathbotApiMessage::Exit(expected)));
+}
+
+#[test]
+fn message() {
+    let expected = Message {
+        message: "There is no exit in that direction.".to_string(),
+        extra: Extra::new(),
+    };
+    assert_eq!(
+        decode_fixture("message.json"),
+        Ok(PathbotApiMessage::Message(expected))
+    );
+}
+
+#[test]
+fn room_extra() {
+    match decode_fixture("room_extra.json") {
+        Ok(PathbotApiMessage::Room(room)) => {
+            let keys: Vec<_> = room.extra.keys().map(String::as_str).collect();
+            assert_eq!(keys, vec!["hazards", "items"]);
+        }
+        other => panic!("not a room: {:?}", other),
+    }
+}
+
+#[test]
+fn room_missing_distance() {
+    assert_eq!(
+        decode_fixture("room_missing_distance.json"),
+        Err(DecodeError::MissingField {
+            variant: "room",
+            field: "mazeExitDistance",
+        })
+    );
+}
+
+#[test]
+fn room_unknown_exit() {
+    match decode_fixture("room_unknown_exit.json") {
+        Err(DecodeError::InvalidField { variant, field, .. }) => {
+            assert_eq!(variant, "room");
+            assert_eq!(field, "exits[1]");
+        }
+        other => panic!("not an invalid exit: {:?}", other),
+    }
+}
+
+#[test]
+fn room_missing_location() {
+    assert_eq!(
+        decode_fixture("room_missing_location.json"),
+        Err(DecodeError::MissingField {
+            variant: "room",
+            field: "locationPath",
+        })
+    );
+}
+
+#[test]
+fn ambiguous() {
+    match decode_fixture("ambiguous.json") {
+        Err(DecodeError::Ambiguous(_)) => {}
+        other => panic!("not ambiguous: {:?}", other),
+    }
+}
+
+#[test]
+fn exit_with_room_fields() {
+    match decode_fixture("exit_with_room_fields.json") {
+        Err(DecodeError::Ambiguous(e)) => assert!(e.contains("mazeExitDistance"), "{}", e),
+        other => panic!("not ambiguous: {:?}", other),
+    }
+}
+
+#[test]
+fn finished_room() {
+    match decode_fixture("finished_room.json") {
+        Err(DecodeError::Ambiguous(_)) => {}
+        other => panic!("not ambiguous: {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_status() {
+    match decode_fixture("unknown_status.json") {
+        Err(DecodeError::InvalidField { field, .. }) => assert_eq!(field, "status"),
+        other => panic!("not an invalid status: {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_variant() {
+    match decode_fixture("unknown_variant.json") {
+        Err(DecodeError::UnknownVariant(_)) => {}
+        other => panic!("not an unknown variant: {:?}", other),
+    }
+}
+
+#[test]
+fn not_an_object() {
+    assert_eq!(decode(&Value::Null), Err(DecodeError::NotAnObject));
+}
+
+#[test]
+fn serde_reports_the_field() {
+    let json = std::fs::read_to_string(format!(
+        "{}/tests/fixtures/room_missing_distance.json",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap();
+    let error = serde_json::from_str::<PathbotApiMessage>(&json).unwrap_err();
+    assert!(error.to_string().contains("mazeExitDistance"), "{}", error);
+}
+
+#[test]
+fn round_trip() {
+    for name in &["room.json", "room_extra.json", "exit.json", "message.json"] {
+        let message = decode_fixture(name).unwrap();
+        let json = serde_json::to_value(&message).unwrap();
+        assert_eq!(decode(&json).unwrap(), message);
+        assert_eq!(serde_json::from_value::<PathbotApiMessage>(json).unwrap(), message);
+    }
+}
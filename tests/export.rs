@@ -0,0 +1,85 @@
+//! Exports of the map of generated mazes
+use pathbot::config::{Backend, Profile};
+use pathbot::export::{self, ExitStatus, MapFormat, Metadata};
+use pathbot::maze::Maze;
+
+mod common;
+use common::explore;
+
+fn profile() -> Profile {
+    Profile {
+        name: "in-memory maze".to_string(),
+        backend: Backend::Memory {
+            width: 6,
+            height: 6,
+            seed: 5,
+        },
+    }
+}
+
+#[test]
+fn metadata() {
+    let maze = Maze::generate(6, 6, 5);
+    let metadata = Metadata::new(&profile(), &explore(&maze, 3), 0.);
+    assert_eq!(metadata.api, "in-memory maze 6x6, seed 5");
+    assert_eq!(metadata.exported_at, "1970-01-01T00:00:00.000Z");
+    assert_eq!(metadata.start_room, Some(maze.start().location_path));
+    assert_eq!(metadata.exit, ExitStatus::Unknown);
+
+    let metadata = Metadata::new(&profile(), &explore(&maze, 10_000), 0.);
+    assert_eq!(metadata.exit, ExitStatus::Escaped);
+    assert_eq!(metadata.start_room, Some(maze.start().location_path));
+}
+
+#[test]
+fn json() {
+    let state = explore(&Maze::generate(6, 6, 5), 10_000);
+    let metadata = Metadata::new(&profile(), &state, 0.);
+    let json = export::to_json(&state, &metadata);
+
+    assert_eq!(json["version"], export::VERSION);
+    assert_eq!(json["metadata"]["exit"], "escaped");
+    let rooms = json["rooms"].as_array().unwrap();
+    assert_eq!(rooms.len(), state.rooms().count());
+    let exit = rooms.iter().find(|room| room["status"] == "finished").unwrap();
+    assert!(exit["exitHint"].is_null());
+    let start = rooms.iter().find(|room| room["x"] == 0 && room["y"] == 0).unwrap();
+    assert!(start["exitHint"]["distance"].is_u64());
+
+    let edges = json["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), state.graph().edges().count());
+    for edge in edges {
+        for end in &["from", "to"] {
+            assert!(rooms.iter().any(|room| room["id"] == edge[end]));
+        }
+    }
+}
+
+#[test]
+fn dot() {
+    let state = explore(&Maze::generate(6, 6, 5), 10_000);
+    let metadata = Metadata::new(&profile(), &state, 0.);
+    let dot = export::export(MapFormat::Dot, &state, &metadata);
+
+    assert!(dot.starts_with("digraph pathbot {"));
+    assert!(dot.ends_with("}\n"));
+    assert_eq!(dot.matches("pos=").count(), state.rooms().count());
+    assert_eq!(dot.matches(" -> ").count(), state.graph().edges().count());
+    // The colors of the map
+    assert!(dot.contains("pos=\"0,0!\", fillcolor=\"#0000ff\""));
+    assert_eq!(dot.matches("#008000").count(), 1);
+    assert_eq!(dot.matches("#ff0000").count(), 0);
+}
+
+#[test]
+fn graphml() {
+    let state = explore(&Maze::generate(6, 6, 5), 10_000);
+    let mut metadata = Metadata::new(&profile(), &state, 0.);
+    metadata.profile = "<local & mock>".to_string();
+    let graphml = export::export(MapFormat::GraphMl, &state, &metadata);
+
+    assert!(graphml.contains("<data key=\"profile\">&lt;local &amp; mock&gt;</data>"));
+    assert_eq!(graphml.matches("<node ").count(), state.rooms().count());
+    assert_eq!(graphml.matches("<edge ").count(), state.graph().edges().count());
+    assert_eq!(graphml.matches("<data key=\"hintDistance\">").count(), state.rooms().count() - 1);
+}
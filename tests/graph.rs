@@ -0,0 +1,165 @@
+//! Room graph built by the moves
+use pathbot::core::*;
+use pathbot::pathbot_api::*;
+
+mod common;
+use common::room;
+
+/// Enters a room, reached from the current room by `last_move`.
+fn walk(
+    state: &mut State,
+    id: &str,
+    exits: Vec<MoveDirection>,
+    last_move: Option<MoveDirection>,
+) -> Option<Conflict> {
+    let conflict = state.insert_room(room(id, exits), last_move);
+    state.move_to(id.to_string());
+    conflict
+}
+
+#[test]
+fn moves_are_confirmed_and_ways_back_inferred() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    state.insert_room(room("a", vec![N, E]), None);
+    state.move_to("a".to_string());
+    state.insert_room(room("b", vec![S]), Some(N));
+    state.move_to("b".to_string());
+
+    let graph = state.graph();
+    let forward = graph.edge("a", N).expect("no edge from a");
+    assert_eq!(forward.to, "b");
+    assert_eq!(forward.kind, EdgeKind::Confirmed);
+    assert_eq!(forward.reverse_direction(), S);
+
+    let back = graph.edge("b", S).expect("no edge back");
+    assert_eq!(back.to, "a");
+    assert_eq!(back.kind, EdgeKind::Inferred);
+
+    assert_eq!(graph.edge("a", E), None);
+    assert_eq!(graph.edges().count(), 2);
+
+    // Only the moves made are known for sure
+    assert_eq!(state.known_destination(S), None);
+    state.move_to("a".to_string());
+    assert_eq!(state.known_destination(N), Some(&"b".to_string()));
+}
+
+#[test]
+fn no_way_back_without_exit() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    state.insert_room(room("a", vec![E]), None);
+    state.move_to("a".to_string());
+    state.insert_room(room("b", vec![N]), Some(E));
+
+    assert_eq!(state.graph().edge("b", W), None);
+    assert_eq!(state.graph().edges_from("a").count(), 1);
+}
+
+#[test]
+fn inferred_edges_dont_replace_confirmed_ones() {
+    use MoveDirection::*;
+    let mut graph = RoomGraph::default();
+    graph.confirm("a".to_string(), N, "b".to_string());
+    assert!(!graph.infer("a".to_string(), N, "c".to_string()));
+    assert_eq!(graph.edge("a", N).unwrap().to, "b");
+}
+
+#[test]
+fn rooms_at_the_same_coordinate_are_kept_apart() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    walk(&mut state, "a", vec![N, E], None);
+    walk(&mut state, "b", vec![S, E], Some(N));
+    walk(&mut state, "c", vec![W, S], Some(E));
+    walk(&mut state, "d", vec![N, W], Some(S));
+    // Not a grid: going West doesn't lead back to "a"
+    let conflict = walk(&mut state, "e", vec![E], Some(W));
+
+    assert_eq!(conflict, Some(Conflict::Overlap("a".to_string())));
+    let origin = Coordinate { x: 0, y: 0 };
+    assert_eq!(state.rooms_at(origin), &["a".to_string(), "e".to_string()]);
+    assert_eq!(state.room_layer(&"e".to_string()), 1);
+    assert_eq!(state.conflicts().count(), 1);
+
+    // The cache follows the moves, not the coordinates
+    state.move_to("d".to_string());
+    assert_eq!(state.known_destination(W), Some(&"e".to_string()));
+    state.move_to("a".to_string());
+    assert_eq!(state.known_destination(E), None);
+    // Nor the inferred ways back
+    state.move_to("e".to_string());
+    assert_eq!(state.graph().edge("e", E).unwrap().to, "d");
+    assert_eq!(state.known_destination(E), None);
+}
+
+#[test]
+fn known_room_reached_elsewhere() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    walk(&mut state, "a", vec![N, E], None);
+    walk(&mut state, "b", vec![S, E], Some(N));
+    walk(&mut state, "c", vec![W, S], Some(E));
+    walk(&mut state, "d", vec![N, W], Some(S));
+    // Not a grid: going West leads back to "b"
+    let conflict = walk(&mut state, "b", vec![S, E], Some(W));
+
+    assert_eq!(
+        conflict,
+        Some(Conflict::Elsewhere {
+            known_at: Coordinate { x: 0, y: -1 },
+            reached_at: Coordinate { x: 0, y: 0 },
+        })
+    );
+    assert_eq!(
+        state.room_coordinates(&"b".to_string()),
+        Some(Coordinate { x: 0, y: -1 })
+    );
+    assert_eq!(state.conflicts().count(), 0);
+    state.move_to("d".to_string());
+    assert_eq!(state.known_destination(W), Some(&"b".to_string()));
+}
+
+#[test]
+fn shortest_path_through_the_graph() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    walk(&mut state, "a", vec![N, E], None);
+    walk(&mut state, "b", vec![S, E], Some(N));
+    walk(&mut state, "c", vec![W], Some(E));
+
+    assert_eq!(state.path_to(&"c".to_string()), Some(vec![]));
+    assert_eq!(
+        state.path_between(&"a".to_string(), &"c".to_string()),
+        Some(vec![N, E])
+    );
+    assert_eq!(state.path_to(&"unknown".to_string()), None);
+
+    // The ways back were not taken yet, but a travel takes them
+    assert_eq!(state.path_to(&"a".to_string()), None);
+    assert_eq!(state.likely_path_to(&"a".to_string()), Some(vec![W, S]));
+    walk(&mut state, "b", vec![S, E], Some(W));
+    walk(&mut state, "a", vec![N, E], Some(S));
+    assert_eq!(
+        state.path_between(&"c".to_string(), &"a".to_string()),
+        Some(vec![W, S])
+    );
+}
+
+#[test]
+fn shortest_path_avoids_inferred_shortcuts() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    walk(&mut state, "a", vec![N, E], None);
+    walk(&mut state, "b", vec![S, E], Some(N));
+    walk(&mut state, "c", vec![W, S], Some(E));
+    walk(&mut state, "d", vec![N, W], Some(S));
+    walk(&mut state, "a", vec![N, E], Some(W));
+
+    let shortcut = state.graph().edge("a", E).expect("no way back");
+    assert_eq!(shortcut.to, "d");
+    assert_eq!(shortcut.kind, EdgeKind::Inferred);
+    assert_eq!(state.path_to(&"d".to_string()), Some(vec![N, E, S]));
+    assert_eq!(state.likely_path_to(&"d".to_string()), Some(vec![E]));
+}
@@ -0,0 +1,47 @@
+//! Going back and forth in the rooms entered
+use pathbot::core::State;
+use pathbot::maze::Maze;
+
+mod common;
+use common::{enter, make_move};
+
+/// Enters the start room, then makes the moves.
+fn walk(maze: &Maze, moves: usize) -> State {
+    let mut state = enter(maze);
+    for _ in 0..moves {
+        let direction = state.current_exits().unwrap()[0];
+        assert!(!make_move(maze, &mut state, direction), "escaped");
+    }
+    state
+}
+
+#[test]
+fn back_and_forth() {
+    let maze = Maze::generate(6, 6, 4);
+    let mut state = walk(&maze, 3);
+    let steps = state.history().steps().to_vec();
+    assert_eq!(steps.len(), 4);
+    assert_eq!(steps[0].direction, None);
+    assert!(steps[1..].iter().all(|step| step.direction.is_some()));
+
+    assert_eq!(state.go_back(), Some(&steps[2].room_id));
+    assert_eq!(state.go_back(), Some(&steps[1].room_id));
+    assert_eq!(state.go_forward(), Some(&steps[2].room_id));
+    assert_eq!(state.current_room_id(), Some(&steps[2].room_id));
+    assert_eq!(state.history().recent(2), &steps[1..3]);
+}
+
+#[test]
+fn moving_after_going_back_forgets_the_rooms_ahead() {
+    let maze = Maze::generate(6, 6, 4);
+    let mut state = walk(&maze, 3);
+    let steps = state.history().steps().to_vec();
+    state.go_back();
+    state.go_back();
+    state.go_back();
+    assert_eq!(state.go_back(), None);
+
+    state.move_to(steps[1].room_id.clone());
+    assert_eq!(state.history().steps(), &steps[..2]);
+    assert!(!state.history().can_go_forward());
+}
@@ -0,0 +1,48 @@
+//! Walls and passages inferred from the known rooms
+use pathbot::core::*;
+use pathbot::pathbot_api::*;
+
+mod common;
+use common::room;
+
+const ORIGIN: Coordinate = Coordinate { x: 0, y: 0 };
+
+#[test]
+fn sides_of_known_and_unknown_rooms() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    state.insert_room(room("a", vec![N, E]), None);
+
+    let inference = state.infer();
+    let known = inference.cell(ORIGIN).unwrap();
+    assert_eq!(known.get(N), Knowledge::ObservedPassage);
+    assert_eq!(known.get(S), Knowledge::ObservedWall);
+    assert!(known.unknown().is_empty());
+
+    let north = inference.cell(Coordinate { x: 0, y: -1 }).unwrap();
+    assert_eq!(north.get(S), Knowledge::InferredPassage);
+    assert_eq!(north.unknown(), vec![N, E, W]);
+    let west = inference.cell(Coordinate { x: -1, y: 0 }).unwrap();
+    assert_eq!(west.get(E), Knowledge::InferredWall);
+
+    assert_eq!(inference.cell(Coordinate { x: 5, y: 5 }), None);
+    assert!(inference.contradictions().is_empty());
+}
+
+#[test]
+fn exit_without_way_back() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    state.insert_room(room("a", vec![N]), None);
+    state.move_to("a".to_string());
+    state.insert_room(room("b", vec![E]), Some(N));
+
+    assert_eq!(
+        state.infer().contradictions(),
+        &[Contradiction {
+            from: "a".to_string(),
+            direction: N,
+            to: "b".to_string(),
+        }]
+    );
+}
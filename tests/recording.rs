@@ -0,0 +1,66 @@
+//! Recordings made from the journal
+use pathbot::journal::{Journal, JournalEntry, RecordedRequest};
+use pathbot::maze::Maze;
+use pathbot::pathbot_api::*;
+use pathbot::recording::Recording;
+
+fn entry(path: &str, body: String, answer: &PathbotApiMessage) -> JournalEntry {
+    let mut entry = JournalEntry::new(RecordedRequest {
+        method: "POST".to_string(),
+        url: format!("memory:{}", path),
+        headers: vec![],
+        body,
+    });
+    entry.received(200, vec![], serde_json::to_string(answer).unwrap());
+    entry
+}
+
+/// Starts the maze, and makes its first move.
+fn play(journal: &mut Journal, maze: &Maze) {
+    let start = maze.start();
+    let direction = start.exits[0];
+    journal.push(entry(
+        "/pathbot/start",
+        "".to_string(),
+        &PathbotApiMessage::Room(start.clone()),
+    ));
+    journal.push(entry(
+        &start.location_path,
+        format!(r#"{{"direction": "{}"}}"#, direction.short_name()),
+        &maze.move_from(&start.location_path, direction).unwrap(),
+    ));
+}
+
+#[test]
+fn only_the_last_session() {
+    let (first, second) = (Maze::generate(4, 4, 1), Maze::generate(5, 5, 2));
+    let mut journal = Journal::default();
+    play(&mut journal, &first);
+    play(&mut journal, &second);
+
+    let recording = Recording::from_journal(&journal).unwrap();
+    assert_eq!(recording.start, PathbotApiMessage::Room(second.start()));
+    assert_eq!(recording.moves.len(), 1);
+    assert!(recording.moves.contains_key(&second.start().location_path));
+}
+
+#[test]
+fn no_start() {
+    assert_eq!(Recording::from_journal(&Journal::default()), None);
+}
+
+#[test]
+fn same_moves_same_file() {
+    let start = Maze::generate(4, 4, 3).start();
+    let answer = PathbotApiMessage::Room(start.clone());
+    let record = |directions: &[MoveDirection]| {
+        let mut recording = Recording::new(answer.clone());
+        for &direction in directions {
+            recording.insert_move(start.location_path.clone(), direction, answer.clone());
+        }
+        serde_json::to_string(&recording).unwrap()
+    };
+    let mut reversed = MoveDirection::ALL;
+    reversed.reverse();
+    assert_eq!(record(&MoveDirection::ALL), record(&reversed));
+}
@@ -0,0 +1,41 @@
+//! Delays between the retries of failed requests
+use pathbot::error::PathbotError;
+use pathbot::retry::RetryPolicy;
+use std::time::Duration;
+
+fn too_many_requests(retry_after: Option<Duration>) -> PathbotError {
+    PathbotError::Http {
+        status: 429,
+        body: "".to_string(),
+        retry_after,
+    }
+}
+
+#[test]
+fn retry_after_is_capped() {
+    let policy = RetryPolicy::default();
+    let error = too_many_requests(Some(Duration::from_secs(2)));
+    assert_eq!(policy.delay(1, &error, 0.5), Some(Duration::from_secs(2)));
+
+    let error = too_many_requests(Some(Duration::from_secs(3 * 3600)));
+    let max_delay = Duration::from_millis(policy.max_delay_ms);
+    assert_eq!(policy.delay(1, &error, 0.5), Some(max_delay));
+}
+
+#[test]
+fn backoff_without_retry_after() {
+    let policy = RetryPolicy {
+        jitter: 0.,
+        ..RetryPolicy::default()
+    };
+    let error = too_many_requests(None);
+    assert_eq!(
+        policy.delay(1, &error, 0.5),
+        Some(Duration::from_millis(500))
+    );
+    assert_eq!(
+        policy.delay(3, &error, 0.5),
+        Some(Duration::from_millis(2000))
+    );
+    assert_eq!(policy.delay(6, &error, 0.5), None);
+}
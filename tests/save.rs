@@ -0,0 +1,96 @@
+//! Saves of explored mazes
+use pathbot::core::State;
+use pathbot::maze::Maze;
+use pathbot::save::{self, Library, Save, SaveError};
+
+mod common;
+use common::explore;
+
+#[test]
+fn round_trip() {
+    let maze = Maze::generate(6, 6, 5);
+    for &moves in &[0, 10, 10_000] {
+        let mut state = explore(&maze, moves);
+        state.go_back();
+        let json = save::encode("in-memory", &state, 1_565_000_000_000.);
+        let save = save::decode(&json).unwrap();
+        assert_eq!(save.profile, "in-memory");
+        assert_eq!(save.state, state);
+    }
+}
+
+#[test]
+fn newer_version() {
+    let json = save::encode("in-memory", &State::default(), 0.)
+        .replace(r#""version":1"#, r#""version":99"#);
+    assert_eq!(save::decode(&json), Err(SaveError::Newer(99)));
+}
+
+#[test]
+fn huge_version() {
+    let json = save::encode("in-memory", &State::default(), 0.)
+        .replace(r#""version":1"#, r#""version":4294967297"#);
+    match save::decode(&json) {
+        Err(SaveError::NotASave(_)) => {}
+        other => panic!("not rejected: {:?}", other),
+    }
+}
+
+#[test]
+fn older_version() {
+    let json = save::encode("in-memory", &State::default(), 0.)
+        .replace(r#""version":1"#, r#""version":0"#);
+    assert_eq!(save::decode(&json), Err(SaveError::Unsupported(0)));
+}
+
+#[test]
+fn not_a_save() {
+    match save::decode(r#"{"rooms": []}"#) {
+        Err(SaveError::NotASave(_)) => {}
+        other => panic!("not rejected: {:?}", other),
+    }
+    match save::decode(r#"{"version": 1, "save": {"profile": "in-memory"}}"#) {
+        Err(SaveError::Invalid(error)) => assert!(error.contains("savedMs"), "{}", error),
+        other => panic!("not rejected: {:?}", other),
+    }
+}
+
+fn saved(moves: usize) -> Save {
+    let state = explore(&Maze::generate(6, 6, 5), moves);
+    Save {
+        profile: "in-memory".to_string(),
+        saved_ms: 1_565_000_000_000.,
+        state,
+    }
+}
+
+#[test]
+fn library() {
+    let mut library = Library::default();
+    let nasty = library.add("nasty", &saved(10), String::new());
+    let escaped = library.add("escaped", &saved(10_000), String::new());
+
+    let slot = library.slot(escaped).unwrap();
+    assert!(slot.exit_found);
+    assert!(!library.slot(nasty).unwrap().exit_found);
+    assert_eq!(library.slot(nasty).unwrap().rooms, saved(10).rooms());
+
+    assert!(library.rename(nasty, " studied "));
+    assert!(!library.rename(nasty, "  "));
+    let copy = library.duplicate(nasty).unwrap();
+    let names: Vec<_> = library.slots().iter().map(|slot| slot.name.as_str()).collect();
+    assert_eq!(names, ["studied", "studied (copy)", "escaped"]);
+
+    library.remove(escaped).unwrap();
+    let demo = library.add("demo", &saved(0), String::new());
+    assert!(demo != escaped && demo != copy);
+    assert_eq!(Library::decode(&library.encode()), Ok(library));
+}
+
+#[test]
+fn slot_names() {
+    assert_eq!(save::slot_name_from_file("nasty maze.json"), "nasty maze");
+    assert_eq!(save::slot_name_from_file("benchmark"), "benchmark");
+    assert_eq!(save::slot_name_from_file(".json"), ".json");
+    assert_eq!(save::slot_name_from_file("a.json.json"), "a.json");
+}
@@ -0,0 +1,44 @@
+//! Maze model, walked through a generated maze
+use pathbot::core::*;
+use pathbot::maze::Maze;
+use pathbot::pathbot_api::*;
+
+mod common;
+use common::enter;
+
+#[test]
+fn rooms_are_placed_by_the_moves() {
+    let maze = Maze::generate(4, 4, 7);
+    let mut state = enter(&maze);
+    let start_id = state.current_room_id().cloned().expect("not in a room");
+    assert_eq!(state.current_coordinates(), Some(Coordinate { x: 0, y: 0 }));
+
+    let direction = state.current_exits().expect("no exits")[0];
+    let next = match maze.move_from(&start_id, direction) {
+        Ok(PathbotApiMessage::Room(room)) => room,
+        answer => panic!("unexpected answer: {:?}", answer),
+    };
+    let next_id = next.location_path.clone();
+    state.insert_room(next, Some(direction));
+    state.move_to(next_id.clone());
+
+    assert_eq!(state.current_room_id(), Some(&next_id));
+    assert_eq!(state.current_coordinates(), Some(direction.delta()));
+    assert_eq!(state.room_at(direction.delta()), Some(&next_id));
+    assert_eq!(state.rooms().count(), 2);
+
+    state.move_to(start_id);
+    assert_eq!(state.known_destination(direction), Some(&next_id));
+}
+
+#[test]
+fn restarting_forgets_the_rooms() {
+    let maze = Maze::generate(4, 4, 7);
+    let mut state = enter(&maze);
+    assert!(!state.loading());
+
+    state.restart();
+    assert!(state.loading());
+    assert_eq!(state.current_room(), None);
+    assert_eq!(state.rooms().count(), 0);
+}
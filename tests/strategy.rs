@@ -0,0 +1,80 @@
+//! The built-in strategies escape generated mazes
+use pathbot::core::State;
+use pathbot::maze::Maze;
+use pathbot::pathbot_api::*;
+use pathbot::strategy::{self, DepthFirst, Strategy};
+
+mod common;
+use common::{enter, make_move, room};
+
+/// Plays like `Model`, and returns the number of moves to the exit.
+fn play(strategy_name: &str, maze: &Maze) -> Option<usize> {
+    let mut strategy = strategy::by_name(strategy_name).unwrap();
+    let mut state = enter(maze);
+    for moves in 1..=10_000 {
+        let direction = strategy.next_move(&state)?;
+        assert!(state.can_move_direction(direction), "{} hit a wall", strategy_name);
+        if make_move(maze, &mut state, direction) {
+            return Some(moves);
+        }
+    }
+    None
+}
+
+#[test]
+fn all_strategies_escape() {
+    for seed in 0..5 {
+        let maze = Maze::generate(10, 10, seed);
+        for name in strategy::NAMES.iter() {
+            assert!(play(name, &maze).is_some(), "{} is lost, seed {}", name, seed);
+        }
+    }
+}
+
+#[test]
+fn unknown_strategy() {
+    assert!(strategy::by_name("teleport").is_none());
+}
+
+#[test]
+fn hints_shorten_the_escape() {
+    let total = |name| -> usize {
+        (0..10)
+            .map(|seed| play(name, &Maze::generate(10, 10, seed)).unwrap())
+            .sum()
+    };
+    assert!(total(strategy::HintGuided::NAME) < total(strategy::DepthFirst::NAME));
+}
+
+#[test]
+fn depth_first_after_a_reset() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    state.insert_room(room("a", vec![N, E]), None);
+    state.move_to("a".to_string());
+    state.insert_room(room("b", vec![S]), Some(N));
+    state.move_to("b".to_string());
+
+    // Nothing to backtrack: goes back to the unexplored exit of "a"
+    assert_eq!(DepthFirst::default().next_move(&state), Some(S));
+}
+
+#[test]
+fn depth_first_after_going_back() {
+    use MoveDirection::*;
+    let mut state = State::default();
+    let mut strategy = DepthFirst::default();
+    state.insert_room(room("a", vec![N]), None);
+    state.move_to("a".to_string());
+    assert_eq!(strategy.next_move(&state), Some(N));
+    state.insert_room(room("b", vec![S, N]), Some(N));
+    state.move_to("b".to_string());
+    assert_eq!(strategy.next_move(&state), Some(N));
+    state.insert_room(room("c", vec![S, E]), Some(N));
+    state.move_to("c".to_string());
+
+    // Doesn't backtrack from "b" through the wall of "a"
+    state.go_back();
+    state.go_back();
+    assert_eq!(strategy.next_move(&state), Some(N));
+}
@@ -0,0 +1,66 @@
+//! Parsing of the room descriptions
+use pathbot::pathbot_api::*;
+
+fn room_fixture() -> Room {
+    let path = format!("{}/tests/fixtures/room.json", env!("CARGO_MANIFEST_DIR"));
+    let json = std::fs::read_to_string(&path).expect("missing fixture");
+    serde_json::from_str(&json).expect("invalid room fixture")
+}
+
+#[test]
+fn parse() {
+    let traits = room_fixture().traits().expect("description not parsed");
+    assert_eq!(traits.color.as_deref(), Some("chartreuse"));
+    assert_eq!(traits.brightness, None);
+    assert_eq!(traits.shape, "rectangular");
+    assert_eq!(traits.kind, "storage room");
+    assert_eq!(traits.exits, vec![MoveDirection::N, MoveDirection::S]);
+    assert_eq!(traits.maze_exit_hint.direction, CompassDirection::N);
+    assert_eq!(traits.maze_exit_hint.distance, 5);
+}
+
+#[test]
+fn parse_brightness_and_single_exit() {
+    let traits = RoomTraits::parse(
+        "You are in a dim round kitchen with an exit to the West. You sense that \
+         the maze's exit is to the South-East, at least 12 rooms away..",
+    )
+    .expect("description not parsed");
+    assert_eq!(traits.color, None);
+    assert_eq!(traits.brightness.as_deref(), Some("dim"));
+    assert_eq!(traits.kind, "kitchen");
+    assert_eq!(traits.exits, vec![MoveDirection::W]);
+    assert_eq!(traits.maze_exit_hint.direction, CompassDirection::SE);
+    assert_eq!(traits.maze_exit_hint.distance, 12);
+}
+
+#[test]
+fn parse_unusual_description() {
+    assert_eq!(RoomTraits::parse("You are lost."), None);
+}
+
+#[test]
+fn mismatches() {
+    let mut room = room_fixture();
+    let traits = room.traits().unwrap();
+    assert_eq!(traits.mismatches(&room), vec![]);
+
+    room.exits = vec![MoveDirection::S, MoveDirection::N];
+    assert_eq!(traits.mismatches(&room), vec![]);
+
+    room.exits = vec![MoveDirection::N];
+    room.maze_exit_hint.distance = 4;
+    assert_eq!(
+        traits.mismatches(&room),
+        vec![
+            TraitMismatch::Exits {
+                described: vec![MoveDirection::N, MoveDirection::S],
+                actual: vec![MoveDirection::N],
+            },
+            TraitMismatch::HintDistance {
+                described: 5,
+                actual: 4,
+            },
+        ]
+    );
+}
@@ -0,0 +1,48 @@
+//! The exit candidates of generated mazes
+use pathbot::core::{Coordinate, State};
+use pathbot::maze::Maze;
+use pathbot::strategy;
+
+mod common;
+use common::{enter, make_move};
+
+/// Escapes the maze, and returns the candidates seen after each move and
+/// the final state.
+fn escape(maze: &Maze) -> (Vec<Vec<Coordinate>>, State) {
+    let mut strategy = strategy::by_name(strategy::DepthFirst::NAME).unwrap();
+    let mut state = enter(maze);
+    let mut seen = vec![state.exit_candidates().cells().collect()];
+    loop {
+        let direction = strategy.next_move(&state).expect("lost");
+        if make_move(maze, &mut state, direction) {
+            return (seen, state);
+        }
+        seen.push(state.exit_candidates().cells().collect());
+    }
+}
+
+#[test]
+fn exit_is_always_a_candidate() {
+    for seed in 0..5 {
+        let (seen, state) = escape(&Maze::generate(8, 8, seed));
+        let exit = state.room_coordinates(&"exit_room_yay".to_string()).unwrap();
+        for candidates in &seen {
+            assert!(candidates.contains(&exit), "seed {}", seed);
+        }
+    }
+}
+
+#[test]
+fn candidates_shrink() {
+    let (seen, _) = escape(&Maze::generate(8, 8, 3));
+    let (first, last) = (seen.first().unwrap(), seen.last().unwrap());
+    assert!(last.len() < first.len());
+}
+
+#[test]
+fn exit_found() {
+    let (_, state) = escape(&Maze::generate(4, 4, 1));
+    let exit = state.room_coordinates(&"exit_room_yay".to_string());
+    assert_eq!(state.exit_candidates().best(), exit);
+    assert_eq!(state.exit_candidates().len(), 1);
+}